@@ -0,0 +1,65 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{request::Parts, HeaderValue},
+    response::Redirect,
+};
+use rand::{distributions::Alphanumeric, Rng};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::RwLock;
+
+use crate::{negotiate, setup::AppState};
+
+/// cookie that hold the session token after the owner login through oauth
+const COOKIE: &str = "session";
+/// a week, login again through /oauth after that
+const SESSION_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// logged in owner session, kept on memory so restarting the server log everyone out
+#[derive(Clone, Default)]
+pub struct Sessions(Arc<RwLock<HashMap<String, Instant>>>);
+
+impl Sessions {
+    /// new session token along with the `Set-Cookie` value for it
+    pub async fn create(&self) -> Option<HeaderValue> {
+        let token = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect::<String>();
+        let cookie = format!(
+            "{COOKIE}={token}; Path=/; Max-Age={}; HttpOnly; SameSite=Lax",
+            SESSION_AGE.as_secs()
+        );
+        let mut sessions = self.0.write().await;
+        sessions.retain(|_, created| created.elapsed() < SESSION_AGE);
+        sessions.insert(token, Instant::now());
+        HeaderValue::from_str(&cookie).ok()
+    }
+    async fn valid(&self, token: &str) -> bool {
+        self.0
+            .read()
+            .await
+            .get(token)
+            .is_some_and(|created| created.elapsed() < SESSION_AGE)
+    }
+}
+
+/// only extracted when the request have a valid owner session, otherwise redirect to login
+pub struct Admin;
+
+#[async_trait]
+impl FromRequestParts<AppState> for Admin {
+    type Rejection = Redirect;
+
+    async fn from_request_parts(parts: &mut Parts, app: &AppState) -> Result<Self, Redirect> {
+        match negotiate::cookie(&parts.headers, COOKIE) {
+            Some(token) if app.sessions.valid(token).await => Ok(Admin),
+            _ => Err(Redirect::temporary("/oauth")),
+        }
+    }
+}
@@ -1,11 +1,27 @@
-use setup::Setup;
+use setup::{Setting, Setup};
 
+pub mod admin;
 pub mod model;
+pub mod negotiate;
 pub mod oauth;
+pub mod report;
 pub mod routes;
 pub mod setup;
+pub mod sitemap;
 
 #[tokio::main]
 async fn main() {
-    Setup::new(routes::reg()).initialize().await
+    if std::env::args().nth(1).as_deref() == Some("translations") {
+        match report::translations().await {
+            Ok(txt) => print!("{txt}"),
+            Err(err) => eprintln!("cant build the translation report: {err:?}"),
+        }
+        return;
+    }
+    let setting = Setting::new()
+        .await
+        .expect("cant locate Setting.toml or its content are invalid");
+    Setup::new(routes::reg(&setting.markdown))
+        .initialize()
+        .await
 }
@@ -1,12 +1,12 @@
 use crate::setup::ThisResult;
 use markdown::Language;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
+/// the pages of each language keyed by the language code ex. `[en.intro]`
 #[derive(Deserialize, Serialize, Clone, Debug)]
-pub struct Content {
-    pub indonesia: Pages,
-    pub english: Pages,
-}
+#[serde(transparent)]
+pub struct Content(pub HashMap<String, Pages>);
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Pages {
@@ -54,10 +54,10 @@ impl Content {
             &tokio::fs::read_to_string("./Content.toml").await?,
         )?)
     }
-    pub fn to_page(self, language: Language) -> Pages {
-        match language {
-            Language::Eng => self.english,
-            Language::Idn => self.indonesia,
-        }
+    /// the pages of the language, or the default language one if its not written yet
+    pub fn to_page(mut self, language: &Language, default: &Language) -> Option<Pages> {
+        self.0
+            .remove(language.code())
+            .or_else(|| self.0.remove(default.code()))
     }
 }
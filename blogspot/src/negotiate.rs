@@ -0,0 +1,135 @@
+use axum::http::{header, HeaderMap, HeaderValue};
+use markdown::Language;
+use tower_http::set_header::SetResponseHeaderLayer;
+
+/// cookie that remember the last language visited
+const COOKIE: &str = "lang";
+/// a year, the choice only change by visiting other language
+const COOKIE_AGE: u32 = 365 * 24 * 60 * 60;
+
+/// "en-US" match "en-US" first and then "en", case insensitive
+fn matching(tag: &str, languages: &[Language]) -> Option<Language> {
+    let tag = tag.trim().to_lowercase();
+    let primary = |code: &str| code.split('-').next().unwrap_or_default().to_owned();
+    languages
+        .iter()
+        .find(|l| l.code().to_lowercase() == tag)
+        .or_else(|| {
+            languages
+                .iter()
+                .find(|l| primary(&l.code().to_lowercase()) == primary(&tag))
+        })
+        .cloned()
+}
+
+/// value of the request cookie
+pub fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|x| x.to_str().ok())
+        .flat_map(|x| x.split(';'))
+        .filter_map(|x| x.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+fn from_cookie(headers: &HeaderMap, languages: &[Language]) -> Option<Language> {
+    let code = cookie(headers, COOKIE)?;
+    languages.iter().find(|l| l.code() == code).cloned()
+}
+
+/// the most preferred language by the quality value, ex. "id-ID,id;q=0.9,en;q=0.8"
+fn from_accept(headers: &HeaderMap, languages: &[Language]) -> Option<Language> {
+    let accept = headers.get(header::ACCEPT_LANGUAGE)?.to_str().ok()?;
+    let mut tags = accept
+        .split(',')
+        .filter_map(|part| {
+            let mut part = part.split(';');
+            let tag = part.next()?.trim();
+            let quality = part
+                .find_map(|x| x.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (!tag.is_empty() && tag != "*" && quality > 0.0).then_some((tag, quality))
+        })
+        .collect::<Vec<_>>();
+    // stable so the header order kept for the same quality
+    tags.sort_by(|a, b| b.1.total_cmp(&a.1));
+    tags.into_iter()
+        .find_map(|(tag, _)| matching(tag, languages))
+}
+
+/// the remembered language first, then the browser preference, then `default`
+pub fn pick(headers: &HeaderMap, languages: &[Language], default: Language) -> Language {
+    from_cookie(headers, languages)
+        .or_else(|| from_accept(headers, languages))
+        .unwrap_or(default)
+}
+
+/// remember the language of the visited page for the next `/` visit, `None` if the code
+/// cant be written on header
+pub fn remember(language: &Language) -> Option<SetResponseHeaderLayer<HeaderValue>> {
+    let cookie = format!("{COOKIE}={language}; Path=/; Max-Age={COOKIE_AGE}; SameSite=Lax");
+    let cookie = HeaderValue::from_str(&cookie).ok()?;
+    Some(SetResponseHeaderLayer::overriding(
+        header::SET_COOKIE,
+        cookie,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn languages() -> Vec<Language> {
+        ["en", "id", "pt-BR"].map(Language::new).to_vec()
+    }
+
+    fn accept(value: &str) -> Option<String> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCEPT_LANGUAGE,
+            HeaderValue::from_str(value).unwrap(),
+        );
+        from_accept(&headers, &languages()).map(|l| l.to_string())
+    }
+
+    #[test]
+    fn highest_quality_first() {
+        assert_eq!(accept("en;q=0.5, id;q=0.9").as_deref(), Some("id"));
+        assert_eq!(accept("fr, en;q=0.8, id;q=0.8").as_deref(), Some("en"));
+        assert_eq!(accept("id;q=0, en;q=0.1").as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn wildcard_ignored() {
+        assert_eq!(accept("*").as_deref(), None);
+        assert_eq!(accept("*, id;q=0.5").as_deref(), Some("id"));
+    }
+
+    #[test]
+    fn region_fall_back_to_primary() {
+        assert_eq!(accept("id-ID").as_deref(), Some("id"));
+        assert_eq!(accept("EN-gb;q=0.9").as_deref(), Some("en"));
+        assert_eq!(accept("pt-BR").as_deref(), Some("pt-BR"));
+        assert_eq!(accept("pt-PT").as_deref(), Some("pt-BR"));
+    }
+
+    #[test]
+    fn malformed_skipped() {
+        assert_eq!(accept("en;q=abc, id;q=0.3").as_deref(), Some("id"));
+        assert_eq!(accept(";;, ,;q=1").as_deref(), None);
+        assert_eq!(accept("fr;q=0.9").as_deref(), None);
+    }
+
+    #[test]
+    fn empty_use_default() {
+        let mut headers = HeaderMap::new();
+        let default = Language::new("en");
+        assert_eq!(pick(&headers, &languages(), default.clone()), default);
+        headers.insert(header::ACCEPT_LANGUAGE, HeaderValue::from_static(""));
+        assert_eq!(pick(&headers, &languages(), default.clone()), default);
+        headers.insert(header::COOKIE, HeaderValue::from_static("lang=id"));
+        assert_eq!(pick(&headers, &languages(), default), Language::new("id"));
+    }
+}
@@ -53,10 +53,10 @@ pub async fn redirect(code: String, app: &AppState) -> Result<Oauth, Myerror> {
         .json::<User>()
         .await?;
 
-    let allowed = &res2.id == &setting.user_id;
+    let allowed = res2.id == setting.user_id;
     if allowed {
         Command::new("sh")
-            .args(&["-c", "npm run flow"])
+            .args(["-c", "npm run flow"])
             .spawn()?
             .wait()
             .await?;
@@ -0,0 +1,39 @@
+use crate::setup::{Setting, ThisResult};
+use markdown::Markdown;
+use std::fmt::Write;
+
+/// text report of the post that miss translation or outdated, run with
+/// `blogspot translations`
+pub async fn translations() -> ThisResult<String> {
+    let setting = Setting::new().await?;
+    let report = Markdown::new(setting.markdown)
+        .await?
+        .translation_report()
+        .await;
+    if report.is_empty() {
+        return Ok("every post is translated\n".into());
+    }
+    let mut txt = String::new();
+    for status in report {
+        let original = &status.original;
+        writeln!(
+            txt,
+            "{}/{} (from /{}/{}/{})",
+            status.post, status.key, original.language, status.post, original.slug
+        )
+        .ok();
+        if !status.missing.is_empty() {
+            let missing = status.missing.iter().map(|l| l.code()).collect::<Vec<_>>();
+            writeln!(txt, "  missing: {}", missing.join(", ")).ok();
+        }
+        for outdated in status.outdated {
+            writeln!(
+                txt,
+                "  outdated: /{}/{}/{}",
+                outdated.language, status.post, outdated.slug
+            )
+            .ok();
+        }
+    }
+    Ok(txt)
+}
@@ -1,15 +1,23 @@
 use crate::{
+    admin::Admin,
     model::{Content, Intro, Portfolio},
+    negotiate,
     oauth::{self, Oauth},
-    setup::{AppState, HtmlOut},
+    setup::{AppState, HtmlOut, Myerror, PageOut},
+    sitemap,
 };
 use axum::{
-    extract::{Path, Query, State},
-    response::Redirect,
+    extract::{Path, Query, RawQuery, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{AppendHeaders, IntoResponse, Redirect},
     routing::get,
     Router,
 };
-use markdown::{Language, PostData, PostList, PostType};
+use markdown::{
+    ArchiveYear, Diagnostic, FeedFormat, Highlight, IndexMap, Language, MetaData, Navigation,
+    PostData, PostDate, PostFilter, PostList, PostType, SearchResult, Series, Site, TagCount,
+    TagMode, Translation,
+};
 use template::PageRender;
 
 #[derive(PageRender)]
@@ -21,38 +29,100 @@ pub enum MyPage {
     // post page
     #[location = "pages/blog.html"]
     Post {
-        post: PostData,
+        post: Box<PostData>,
         language: Language,
         name: PostType,
+        highlight: Highlight,
+        /// shown from the fallback language since its not translated yet
+        untranslated: bool,
+        slug: String,
+        /// the same post on every language for the language switcher
+        translations: Vec<Translation>,
+        navigation: Box<Navigation>,
+        /// every part when the post is part of series
+        series: Option<Series>,
     },
 
     #[location = "pages/intro.html"]
-    Intro { data: Intro, language: Language },
+    Intro {
+        data: Intro,
+        /// the featured work strip
+        featured: IndexMap<String, MetaData>,
+        /// collection of the featured post, for the link
+        post: PostType,
+        language: Language,
+    },
 
     #[location = "pages/portofolio.html"]
     Portofolio { data: Portfolio, language: Language },
 
-    #[location = "pages/blog_list.html"]
+    // the template come from the collection config
+    #[location_field = "template"]
     List {
-        list: PostList,
+        template: String,
+        list: Box<PostList>,
         post: PostType,
         tag: Option<String>,
         language: Language,
+        /// the tags and date range from the query
+        filter: PostFilter,
+        /// appended to the pagination link so the filter kept, empty or start with `?`
+        query: String,
+        /// post count per year and month for the sidebar
+        archive: Vec<ArchiveYear>,
+    },
+    #[location = "pages/archive.html"]
+    Archive {
+        /// post of the year or month, `None` on the archive index
+        posts: Option<IndexMap<String, MetaData>>,
+        archive: Vec<ArchiveYear>,
+        year: Option<i32>,
+        month: Option<u32>,
+        post: PostType,
+        language: Language,
+    },
+    #[location = "pages/tags.html"]
+    Tags {
+        tags: Vec<TagCount>,
+        post: PostType,
+        language: Language,
+    },
+    #[location = "pages/series.html"]
+    SeriesList {
+        series: Vec<Series>,
+        language: Language,
+    },
+    #[location = "pages/search.html"]
+    Search {
+        result: SearchResult,
+        query: String,
+        language: Language,
     },
     #[location = "pages/oauth.html"]
     Oauth { data: Oauth },
+    #[location = "pages/diagnostics.html"]
+    Diagnostics { diagnostics: Vec<Diagnostic> },
 }
 
 async fn index(app: AppState, language: Language) -> HtmlOut {
-    let content = Content::new().await?.to_page(language);
+    let default = app.markdown.default_language().await;
+    let Some(content) = Content::new().await?.to_page(&language, &default) else {
+        return app.render(MyPage::E404).await;
+    };
+    let featured = app.markdown.featured(language.clone()).await;
     app.render(MyPage::Intro {
         data: content.intro,
+        featured,
+        post: app.markdown.featured_collection().await,
         language,
     })
     .await
 }
 async fn portofolio(app: AppState, language: Language) -> HtmlOut {
-    let content = Content::new().await?.to_page(language);
+    let default = app.markdown.default_language().await;
+    let Some(content) = Content::new().await?.to_page(&language, &default) else {
+        return app.render(MyPage::E404).await;
+    };
     app.render(MyPage::Portofolio {
         data: content.portfolio,
         language,
@@ -60,23 +130,137 @@ async fn portofolio(app: AppState, language: Language) -> HtmlOut {
     .await
 }
 
-async fn render_post(app: AppState, slug: String, post: PostType, language: Language) -> HtmlOut {
-    app.render(MyPage::Post {
-        post: app.markdown.get_post(language, post, slug).await?,
-        language,
-        name: post,
-    })
-    .await
+async fn render_post(
+    app: AppState,
+    slug: String,
+    post: PostType,
+    language: Language,
+    preview: bool,
+) -> PageOut {
+    // the hidden post answer the same as the missing one
+    let data = app
+        .markdown
+        .get_post(language.clone(), post.clone(), &slug, preview)
+        .await;
+    let data = match data {
+        Err(markdown::Error::FileError) => return not_found(&app).await,
+        data => data?,
+    };
+    // the fallback post belong to the series on its own language
+    let series = match &data.metadata.series {
+        Some(series) => {
+            let from = data.metadata.fallback_language.clone();
+            let current = Some((&post, slug.as_str()));
+            app.markdown
+                .series(
+                    from.unwrap_or(language.clone()),
+                    &series.name,
+                    current,
+                    preview,
+                )
+                .await
+        }
+        None => None,
+    };
+    let html = app
+        .render(MyPage::Post {
+            series,
+            untranslated: data.metadata.fallback_language.is_some(),
+            translations: app
+                .markdown
+                .translations(language.clone(), post.clone(), &slug)
+                .await,
+            navigation: Box::new(
+                app.markdown
+                    .navigation(language.clone(), post.clone(), &slug, preview)
+                    .await,
+            ),
+            slug,
+            post: Box::new(data),
+            language,
+            name: post,
+            highlight: app.markdown.highlight().await,
+        })
+        .await?;
+    Ok(html.into_response())
 }
 
-async fn list(app: AppState, page: usize, post: PostType, language: Language) -> HtmlOut {
-    app.render(MyPage::List {
-        list: app.markdown.list(language, post, page).await,
-        post,
-        tag: None,
-        language,
-    })
-    .await
+/// list template of the collection, the default one if its no longer configured
+async fn list_template(app: &AppState, post: &PostType) -> String {
+    match app.markdown.collection(post).await {
+        Some(collection) => collection.template,
+        None => "pages/blog_list.html".into(),
+    }
+}
+
+/// `?tags=rust,-draft&mode=any&from=2024-01-01&to=2024-12-31`, the tag starting with `-`
+/// excluded
+#[derive(serde::Deserialize)]
+struct ListQuery {
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    mode: TagMode,
+    from: Option<PostDate>,
+    to: Option<PostDate>,
+    /// the whole query kept for the pagination link
+    #[serde(skip)]
+    raw: Option<String>,
+}
+
+impl ListQuery {
+    fn parse(raw: Option<String>) -> Result<Self, serde_urlencoded::de::Error> {
+        let mut params: Self = serde_urlencoded::from_str(raw.as_deref().unwrap_or_default())?;
+        params.raw = raw;
+        Ok(params)
+    }
+}
+
+/// the 404 page with the proper status, for the page number past the last page
+async fn not_found(app: &AppState) -> PageOut {
+    Ok((StatusCode::NOT_FOUND, app.render(MyPage::E404).await?).into_response())
+}
+
+async fn list(
+    app: AppState,
+    page: usize,
+    post: PostType,
+    language: Language,
+    preview: bool,
+    params: ListQuery,
+) -> PageOut {
+    let filter = PostFilter::new(&params.tags, params.mode).with_dates(params.from, params.to);
+    let list = app
+        .markdown
+        .list_filtered(
+            language.clone(),
+            post.clone(),
+            filter.clone(),
+            page,
+            preview,
+        )
+        .await;
+    let list = match list {
+        Err(markdown::Error::PageNotFound { .. }) => return not_found(&app).await,
+        list => list?,
+    };
+    let archive = app
+        .markdown
+        .archive(language.clone(), post.clone(), preview)
+        .await;
+    let html = app
+        .render(MyPage::List {
+            template: list_template(&app, &post).await,
+            list: Box::new(list),
+            post,
+            tag: None,
+            query: params.raw.map(|x| format!("?{x}")).unwrap_or_default(),
+            filter,
+            archive,
+            language,
+        })
+        .await?;
+    Ok(html.into_response())
 }
 
 pub async fn error(State(app): State<AppState>) -> HtmlOut {
@@ -89,30 +273,219 @@ async fn list_tag(
     post: PostType,
     language: Language,
     tag: String,
-) -> HtmlOut {
-    app.render(MyPage::List {
-        list: app.markdown.list_from_tag(language, post, &tag, page).await,
+    preview: bool,
+) -> PageOut {
+    let list = app
+        .markdown
+        .list_from_tag(language.clone(), post.clone(), &tag, page, preview)
+        .await;
+    let list = match list {
+        Err(markdown::Error::PageNotFound { .. }) => return not_found(&app).await,
+        list => list?,
+    };
+    let archive = app
+        .markdown
+        .archive(language.clone(), post.clone(), preview)
+        .await;
+    let html = app
+        .render(MyPage::List {
+            template: list_template(&app, &post).await,
+            list: Box::new(list),
+            post,
+            tag: Some(tag),
+            query: String::new(),
+            filter: PostFilter::default(),
+            archive,
+            language,
+        })
+        .await?;
+    Ok(html.into_response())
+}
+
+/// the year and month index, or the post written on the year or month
+async fn archive(
+    app: AppState,
+    post: PostType,
+    language: Language,
+    period: Option<(i32, Option<u32>)>,
+    preview: bool,
+) -> PageOut {
+    let posts = match period {
+        Some((year, month)) => {
+            let posts = app
+                .markdown
+                .archive_posts(language.clone(), post.clone(), year, month, preview)
+                .await;
+            match posts {
+                Some(posts) => Some(posts),
+                None => return not_found(&app).await,
+            }
+        }
+        None => None,
+    };
+    let archive = app
+        .markdown
+        .archive(language.clone(), post.clone(), preview)
+        .await;
+    let html = app
+        .render(MyPage::Archive {
+            posts,
+            archive,
+            year: period.map(|(year, _)| year),
+            month: period.and_then(|(_, month)| month),
+            post,
+            language,
+        })
+        .await?;
+    Ok(html.into_response())
+}
+
+async fn tags(app: AppState, post: PostType, language: Language, preview: bool) -> HtmlOut {
+    app.render(MyPage::Tags {
+        tags: app
+            .markdown
+            .tags(language.clone(), post.clone(), preview)
+            .await,
         post,
-        tag: Some(tag),
         language,
     })
     .await
 }
 
-async fn page_or_list(app: AppState, slug: String, post: PostType, language: Language) -> HtmlOut {
+/// the admin also see the post that not public yet, only the list read the query string
+/// so the post page ignore it
+async fn page_or_list(
+    app: AppState,
+    slug: String,
+    post: PostType,
+    language: Language,
+    admin: Option<Admin>,
+    query: Option<String>,
+) -> PageOut {
+    let preview = admin.is_some();
     if let Ok(page) = slug.parse::<usize>() {
-        list(app, page, post, language).await
+        let filter = match ListQuery::parse(query) {
+            Ok(filter) => filter,
+            Err(e) => {
+                let message = format!("Failed to deserialize query string: {e}");
+                return Ok((StatusCode::BAD_REQUEST, message).into_response());
+            }
+        };
+        list(app, page, post, language, preview, filter).await
+    } else if let Some(translated) = app
+        .markdown
+        .translated_slug(language.clone(), post.clone(), &slug, preview)
+        .await
+    {
+        // the fallback slug of post that already translated with other slug
+        Ok(Redirect::temporary(&format!("/{language}/{post}/{translated}")).into_response())
     } else {
-        render_post(app, slug, post, language).await
+        render_post(app, slug, post, language, preview).await
     }
 }
+async fn feed(
+    app: AppState,
+    format: FeedFormat,
+    post: PostType,
+    language: Language,
+    tag: Option<String>,
+) -> impl IntoResponse {
+    let setting = app.setting.read().await;
+    let site = Site {
+        base_url: &setting.base_url,
+        name: &setting.site_name,
+    };
+    let xml = match tag {
+        Some(tag) => {
+            app.markdown
+                .feed_from_tag(format, site, language, post, tag)
+                .await
+        }
+        None => app.markdown.feed(format, site, language, post).await,
+    };
+    ([(header::CONTENT_TYPE, format.content_type())], xml)
+}
+
+#[derive(serde::Deserialize)]
+struct SearchQuery {
+    #[serde(default)]
+    q: String,
+    page: Option<usize>,
+}
+async fn series(app: AppState, language: Language, admin: Option<Admin>) -> HtmlOut {
+    app.render(MyPage::SeriesList {
+        series: app
+            .markdown
+            .all_series(language.clone(), admin.is_some())
+            .await,
+        language,
+    })
+    .await
+}
+
+async fn search(app: AppState, language: Language, params: SearchQuery) -> PageOut {
+    let result = app
+        .markdown
+        .search(language.clone(), &params.q, params.page.unwrap_or(1))
+        .await;
+    let result = match result {
+        Err(markdown::Error::PageNotFound { .. }) => return not_found(&app).await,
+        result => result?,
+    };
+    let html = app
+        .render(MyPage::Search {
+            result,
+            query: params.q,
+            language,
+        })
+        .await?;
+    Ok(html.into_response())
+}
+
 #[derive(serde::Deserialize)]
 struct QueryCode {
     code: String,
 }
-async fn callback(State(app): State<AppState>, Query(params): Query<QueryCode>) -> HtmlOut {
+async fn callback(
+    State(app): State<AppState>,
+    Query(params): Query<QueryCode>,
+) -> Result<impl IntoResponse, Myerror> {
     let data = oauth::redirect(params.code.to_owned(), &app).await?;
-    app.render(MyPage::Oauth { data }).await
+    // the owner stay logged in for the admin page
+    let session = match data.allowed {
+        true => app.sessions.create().await,
+        false => None,
+    };
+    let cookie = AppendHeaders(session.map(|x| (header::SET_COOKIE, x)));
+    Ok((cookie, app.render(MyPage::Oauth { data }).await?))
+}
+
+async fn diagnostics(_: Admin, State(app): State<AppState>) -> HtmlOut {
+    app.render(MyPage::Diagnostics {
+        diagnostics: app.markdown.diagnostics().await,
+    })
+    .await
+}
+
+async fn highlight_css(State(app): State<AppState>) -> Result<impl IntoResponse, Myerror> {
+    Ok((
+        [(header::CONTENT_TYPE, "text/css")],
+        app.markdown.highlight_css().await?,
+    ))
+}
+
+async fn sitemap_xml(State(app): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "application/xml; charset=utf-8")],
+        sitemap::sitemap(&app).await,
+    )
+}
+
+async fn robots_txt(State(app): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        sitemap::robots(&*app.setting.read().await),
+    )
 }
 
 async fn oauth2(State(app): State<AppState>) -> Redirect {
@@ -120,48 +493,179 @@ async fn oauth2(State(app): State<AppState>) -> Redirect {
 }
 
 fn post_route(language: Language, post: PostType) -> Router<AppState> {
+    let at = (language, post);
+    let page = at.clone();
+    let rss = at.clone();
+    let atom = at.clone();
+    let slug = at.clone();
+    let tag_rss = at.clone();
+    let tag_cloud = at.clone();
+    let archive_index = at.clone();
+    let archive_year = at.clone();
+    let archive_month = at.clone();
     Router::new()
         .route(
             "/",
-            get(move |State(app): State<AppState>| page_or_list(app, "1".into(), post, language)),
+            get(
+                move |State(app): State<AppState>,
+                      admin: Option<Admin>,
+                      RawQuery(raw): RawQuery| {
+                    let (language, post) = page.clone();
+                    page_or_list(app, "1".into(), post, language, admin, raw)
+                },
+            ),
+        )
+        .route(
+            "/feed.xml",
+            get(move |State(app): State<AppState>| {
+                let (language, post) = rss.clone();
+                feed(app, FeedFormat::Rss, post, language, None)
+            }),
+        )
+        .route(
+            "/atom.xml",
+            get(move |State(app): State<AppState>| {
+                let (language, post) = atom.clone();
+                feed(app, FeedFormat::Atom, post, language, None)
+            }),
         )
         .route(
             "/:slug",
             get(
-                move |State(app): State<AppState>, Path(slug): Path<String>| {
-                    page_or_list(app, slug, post, language)
+                move |State(app): State<AppState>,
+                      Path(name): Path<String>,
+                      admin: Option<Admin>,
+                      RawQuery(raw): RawQuery| {
+                    let (language, post) = slug.clone();
+                    page_or_list(app, name, post, language, admin, raw)
                 },
             ),
         )
         .route(
-            "/tag/:tag/:page",
+            "/archive",
+            get(move |State(app): State<AppState>, admin: Option<Admin>| {
+                let (language, post) = archive_index.clone();
+                archive(app, post, language, None, admin.is_some())
+            }),
+        )
+        .route(
+            "/archive/:year",
             get(
-                move |State(app): State<AppState>, Path((tag, page)): Path<(String, usize)>| {
-                    list_tag(app, page, post, language, tag)
+                move |State(app): State<AppState>, Path(year): Path<i32>, admin: Option<Admin>| {
+                    let (language, post) = archive_year.clone();
+                    archive(app, post, language, Some((year, None)), admin.is_some())
                 },
             ),
         )
-}
-
-fn lang_route(language: Language) -> Router<AppState> {
-    Router::new()
         .route(
-            "/",
-            get(move |State(app): State<AppState>| index(app, language)),
+            "/archive/:year/:month",
+            get(
+                move |State(app): State<AppState>,
+                      Path((year, month)): Path<(i32, u32)>,
+                      admin: Option<Admin>| {
+                    let (language, post) = archive_month.clone();
+                    let period = Some((year, Some(month)));
+                    archive(app, post, language, period, admin.is_some())
+                },
+            ),
+        )
+        .route(
+            "/tags",
+            get(move |State(app): State<AppState>, admin: Option<Admin>| {
+                let (language, post) = tag_cloud.clone();
+                tags(app, post, language, admin.is_some())
+            }),
+        )
+        .route(
+            "/tag/:tag/feed.xml",
+            get(
+                move |State(app): State<AppState>, Path(tag): Path<String>| {
+                    let (language, post) = tag_rss.clone();
+                    feed(app, FeedFormat::Rss, post, language, Some(tag))
+                },
+            ),
         )
         .route(
-            "/portfolio",
-            get(move |State(app): State<AppState>| portofolio(app, language)),
+            "/tag/:tag/:page",
+            get(
+                move |State(app): State<AppState>,
+                      Path((tag, page)): Path<(String, usize)>,
+                      admin: Option<Admin>| {
+                    let (language, post) = at.clone();
+                    list_tag(app, page, post, language, tag, admin.is_some())
+                },
+            ),
         )
-        .nest("/blog", post_route(language, PostType::Blog))
-        .nest("/project", post_route(language, PostType::Project))
 }
 
-pub fn reg() -> Router<AppState> {
-    Router::new()
-        .route("/", get(|| async { Redirect::permanent("/en") }))
-        .nest("/en", lang_route(Language::Eng))
-        .nest("/id", lang_route(Language::Idn))
+/// path under `/{code}` that the collection prefix cant use
+pub const RESERVED: &[&str] = &["portfolio", "series", "search"];
+
+fn lang_route(language: Language, posts: &[PostType]) -> Router<AppState> {
+    let lang = language.clone();
+    let index = get(move |State(app): State<AppState>| index(app, lang.clone()));
+    let lang = language.clone();
+    let portfolio = get(move |State(app): State<AppState>| portofolio(app, lang.clone()));
+    let lang = language.clone();
+    let search = get(
+        move |State(app): State<AppState>, Query(params): Query<SearchQuery>| {
+            search(app, lang.clone(), params)
+        },
+    );
+    let lang = language.clone();
+    let series = get(move |State(app): State<AppState>, admin: Option<Admin>| {
+        series(app, lang.clone(), admin)
+    });
+    let mut router = Router::new()
+        .route("/", index)
+        .route("/portfolio", portfolio)
+        .route("/series", series)
+        .route("/search", search);
+    for post in posts {
+        router = router.nest(
+            &format!("/{post}"),
+            post_route(language.clone(), post.clone()),
+        );
+    }
+    match negotiate::remember(&language) {
+        Some(cookie) => router.layer(cookie),
+        None => router,
+    }
+}
+
+/// the remembered or the browser preferred language, temporary since both can change
+async fn root(State(app): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
+    let languages = app.markdown.languages().await;
+    let default = app.markdown.default_language().await;
+    let language = negotiate::pick(&headers, &languages, default);
+    (
+        [(header::VARY, "Accept-Language, Cookie")],
+        Redirect::temporary(&format!("/{language}")),
+    )
+}
+
+/// path on the root that the language code cant use
+pub const RESERVED_ROOT: &[&str] = &[
+    "oauth",
+    "callback",
+    "admin",
+    "highlight.css",
+    "sitemap.xml",
+    "robots.txt",
+];
+
+/// every language and collection on the config get its own route, so adding one need restart
+pub fn reg(config: &markdown::Config) -> Router<AppState> {
+    let mut router = Router::new().route("/", get(root));
+    let posts = config.post_types();
+    for language in config.languages() {
+        router = router.nest(&format!("/{language}"), lang_route(language, &posts));
+    }
+    router
         .route("/oauth", get(oauth2))
         .route("/callback", get(callback))
+        .route("/admin/diagnostics", get(diagnostics))
+        .route("/highlight.css", get(highlight_css))
+        .route("/sitemap.xml", get(sitemap_xml))
+        .route("/robots.txt", get(robots_txt))
 }
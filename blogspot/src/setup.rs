@@ -1,16 +1,30 @@
 use axum::{
     handler::Handler,
-    response::{Html, IntoResponse},
+    response::{Html, IntoResponse, Response},
     Router,
 };
-use markdown::Markdown;
-use std::sync::Arc;
-use template::{PageRender, Templates};
+use markdown::{Diagnostic, Markdown};
+use notify_debouncer_full::{
+    new_debouncer,
+    notify::{self, event::ModifyKind, EventKind, RecursiveMode},
+    DebounceEventResult,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use template::{tera, PageRender, Templates};
 use thiserror::Error;
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, RwLock};
 use tower_http::services::ServeDir;
 
-use crate::routes::error;
+use crate::{
+    admin::Sessions,
+    routes::{self, error},
+    sitemap::Robots,
+};
 
 #[derive(serde::Deserialize, Clone)]
 pub struct Setting {
@@ -19,6 +33,66 @@ pub struct Setting {
     pub oauth_url: String,
     pub user_id: usize,
     pub client_id: String,
+    /// public url of the site without trailing slash, used for absolute url on feed
+    pub base_url: String,
+    /// site name shown on the feed title
+    pub site_name: String,
+    #[serde(default)]
+    pub robots: Robots,
+    /// reload content automatically when the file changed
+    #[serde(default)]
+    pub watch: bool,
+    /// how long to wait for the file to settle before reloading
+    #[serde(default = "default_debounce")]
+    pub watch_debounce_ms: u64,
+    /// markdown loading option, flattened so it live on the top level of Setting.toml
+    #[serde(flatten)]
+    pub markdown: markdown::Config,
+}
+
+fn default_debounce() -> u64 {
+    500
+}
+
+impl Setting {
+    pub async fn new() -> ThisResult<Self> {
+        let setting: Self = toml::from_str(&tokio::fs::read_to_string("./Setting.toml").await?)?;
+        setting.validate()?;
+        Ok(setting)
+    }
+    /// the language code become `/{code}` and the collection prefix `/{code}/{prefix}`,
+    /// so they must not clash with the other route or each other
+    fn validate(&self) -> ThisResult<()> {
+        if self.markdown.languages.is_empty() {
+            return Err(Myerror::Setting("at least one language needed".into()));
+        }
+        let codes = self.markdown.languages.iter().map(|l| l.code.as_str());
+        segments("language code", codes, routes::RESERVED_ROOT)?;
+        let prefixes = self.markdown.collections.iter().map(|c| c.prefix.as_str());
+        segments("collection prefix", prefixes, routes::RESERVED)
+    }
+}
+
+/// each name must be unique single path segment that not `reserved`
+fn segments<'a>(
+    what: &str,
+    names: impl Iterator<Item = &'a str>,
+    reserved: &[&str],
+) -> ThisResult<()> {
+    let mut seen = HashSet::new();
+    for name in names {
+        let reason = if name.is_empty() || name.contains('/') {
+            "must be single path segment"
+        } else if reserved.contains(&name) {
+            "is already used by other page"
+        } else if !seen.insert(name) {
+            "is used more than once"
+        } else {
+            continue;
+        };
+        return Err(Myerror::Setting(format!("{what} `{name}` {reason}")));
+    }
+    Ok(())
 }
 
 #[derive(Clone)]
@@ -26,6 +100,7 @@ pub struct AppState {
     pub template: Templates,
     pub markdown: Markdown,
     pub setting: Arc<RwLock<Setting>>,
+    pub sessions: Sessions,
 }
 
 #[derive(Error, Debug)]
@@ -40,6 +115,10 @@ pub enum Myerror {
     Tokio(#[from] tokio::io::Error),
     #[error("reqwest channel error")]
     Reqwest(#[from] reqwest::Error),
+    #[error("file watcher error")]
+    Watch(#[from] notify::Error),
+    #[error("invalid Setting.toml: {0}")]
+    Setting(String),
 }
 
 impl IntoResponse for Myerror {
@@ -51,28 +130,204 @@ impl IntoResponse for Myerror {
 
 pub type ThisResult<T> = Result<T, Myerror>;
 pub type HtmlOut = ThisResult<Html<String>>;
+/// for page that can answer with other status than 200
+pub type PageOut = ThisResult<Response>;
+
+/// `languages()` and `default_language()` for the language switcher on every page,
+/// they follow the routes so both only change on restart
+async fn register_languages(template: &Templates, config: &markdown::Config) {
+    let languages = tera::to_value(&config.languages).unwrap_or_default();
+    let default = tera::to_value(config.default_language()).unwrap_or_default();
+    let mut tera = template.write().await;
+    tera.register_function("languages", move |_: &HashMap<String, tera::Value>| {
+        Ok(languages.clone())
+    });
+    tera.register_function(
+        "default_language",
+        move |_: &HashMap<String, tera::Value>| Ok(default.clone()),
+    );
+}
+
+/// `collections()` for the header nav, each with `prefix` and `name`. like the language
+/// they follow the routes
+async fn register_collections(template: &Templates, config: &markdown::Config) {
+    let collections = config
+        .collections
+        .iter()
+        .map(|c| HashMap::from([("prefix", c.prefix.as_str()), ("name", c.name())]))
+        .collect::<Vec<_>>();
+    let collections = tera::to_value(collections).unwrap_or_default();
+    let mut tera = template.write().await;
+    tera.register_function("collections", move |_: &HashMap<String, tera::Value>| {
+        Ok(collections.clone())
+    });
+}
 
 impl AppState {
     async fn new() -> ThisResult<Self> {
         let template = Templates::default();
-        let markdown = Markdown::new().await?;
-        let setting = Arc::new(RwLock::new(
-            toml::from_str::<Setting>(
-                &tokio::fs::read_to_string("./Setting.toml")
-                    .await
-                    .expect("cant locate Setting.toml on project folder"),
-            )
-            .expect("the content of Setting.toml are invalid"),
-        ));
+        let setting = Setting::new()
+            .await
+            .expect("cant locate Setting.toml or its content are invalid");
+        register_languages(&template, &setting.markdown).await;
+        register_collections(&template, &setting.markdown).await;
+        let markdown = Markdown::new(setting.markdown.clone()).await?;
+        let setting = Arc::new(RwLock::new(setting));
         Ok(Self {
             template,
             markdown,
             setting,
+            sessions: Sessions::default(),
         })
     }
     pub async fn render(&self, page: impl PageRender) -> HtmlOut {
         Ok(Html(self.template.render(page).await?))
     }
+    /// reload only what changed, on error the old one keep serving and the error logged
+    async fn reload_changed(&self, mut changed: Changed) {
+        if !(changed.setting || !changed.markdown.is_empty() || changed.template || changed.content)
+        {
+            return;
+        }
+        if changed.setting {
+            match Setting::new().await {
+                Ok(mut setting) => {
+                    let old = self.setting.read().await.clone();
+                    if old.listen_addr != setting.listen_addr {
+                        log::warn!("listen_addr change need server restart to take effect");
+                    }
+                    if old.markdown.languages != setting.markdown.languages
+                        || old.markdown.default_language != setting.markdown.default_language
+                    {
+                        log::warn!("languages change need server restart to take effect");
+                        // keep serving the one the routes built from until then
+                        setting.markdown.languages = old.markdown.languages.clone();
+                        setting.markdown.default_language = old.markdown.default_language.clone();
+                    }
+                    if old.markdown.markdown_dir != setting.markdown.markdown_dir && old.watch {
+                        log::warn!("markdown_dir change need server restart to be watched");
+                    }
+                    // page size, template and order apply right away but new or removed
+                    // collection need new routes
+                    if old.markdown.post_types() != setting.markdown.post_types() {
+                        log::warn!("collections change need server restart to take effect");
+                        setting.markdown.collections = old.markdown.collections.clone();
+                    }
+                    // the markdown need to be parsed again on new option
+                    if old.markdown != setting.markdown {
+                        match self.markdown.reload_with(setting.markdown.clone()).await {
+                            Ok(_) => {
+                                log::info!("markdown reloaded with new setting");
+                                changed.markdown.clear();
+                            }
+                            Err(err) => log::error!("cant reload markdown: {err:?}"),
+                        }
+                    }
+                    *self.setting.write().await = setting;
+                    log::info!("Setting.toml reloaded");
+                }
+                Err(err) => log::error!("cant reload Setting.toml: {err:?}"),
+            }
+        }
+        let mut full = false;
+        for path in &changed.markdown {
+            match self.markdown.reload_file(path).await {
+                Ok(_) => log::info!("markdown reloaded: {}", path.display()),
+                // the post itself is broken, reloading everything would fail the same way
+                Err(err) if Diagnostic::new(&err).is_some() => {
+                    log::error!("cant reload markdown: {err}")
+                }
+                // not a post file (ex. new folder) so just check everything
+                Err(_) => full = true,
+            }
+        }
+        if full {
+            match self.markdown.reload().await {
+                Ok(_) => log::info!("markdown reloaded"),
+                Err(err) => log::error!("cant reload markdown: {err:?}"),
+            }
+        }
+        if changed.template {
+            match self.template.reload().await {
+                Ok(_) => log::info!("templates reloaded"),
+                Err(err) => log::error!("cant reload templates: {err:?}"),
+            }
+        }
+        // Content.toml is read on each request, so there is nothing to reload
+        if changed.content {
+            log::info!("Content.toml changed");
+        }
+    }
+}
+
+/// what kind of file changed on single debounced batch
+#[derive(Default, Debug)]
+struct Changed {
+    markdown: Vec<PathBuf>,
+    template: bool,
+    content: bool,
+    setting: bool,
+}
+
+impl Changed {
+    /// `markdown` is the canonical `markdown_dir`, it can be outside the root
+    fn new(root: &Path, markdown: &Path, paths: impl Iterator<Item = PathBuf>) -> Self {
+        let mut changed = Self::default();
+        for path in paths {
+            if path.starts_with(markdown) {
+                if !changed.markdown.contains(&path) {
+                    changed.markdown.push(path);
+                }
+            } else if path.starts_with(root.join("pages").join("templates")) {
+                changed.template = true;
+            } else if path == root.join("Content.toml") {
+                changed.content = true;
+            } else if path == root.join("Setting.toml") {
+                changed.setting = true;
+            }
+        }
+        changed
+    }
+}
+
+/// watch the content and setting file, then reload what changed after they settle
+async fn watch(state: AppState) -> ThisResult<()> {
+    let root = std::fs::canonicalize(".")?;
+    // the folder is fixed once watched, changing it need restart
+    let markdown = std::fs::canonicalize(&state.setting.read().await.markdown.markdown_dir)?;
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let debounce = Duration::from_millis(state.setting.read().await.watch_debounce_ms);
+    let mut debouncer = new_debouncer(debounce, None, move |res: DebounceEventResult| {
+        tx.send(res).ok();
+    })?;
+    debouncer.watch(&markdown, RecursiveMode::Recursive)?;
+    debouncer.watch(
+        root.join("pages").join("templates"),
+        RecursiveMode::Recursive,
+    )?;
+    // the toml files are watched from the folder, editor often replace the file on save
+    debouncer.watch(&root, RecursiveMode::NonRecursive)?;
+    log::info!("watching content for changes");
+    while let Some(res) = rx.recv().await {
+        match res {
+            Ok(events) => {
+                // reading the file on reload also trigger access/metadata event, skip them
+                // or it will keep reloading itself
+                let paths = events
+                    .into_iter()
+                    .filter(|e| match e.kind {
+                        EventKind::Modify(ModifyKind::Metadata(_)) => false,
+                        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => true,
+                        _ => false,
+                    })
+                    .flat_map(|e| e.event.paths);
+                let changed = Changed::new(&root, &markdown, paths);
+                state.reload_changed(changed).await;
+            }
+            Err(err) => log::error!("file watcher error: {err:?}"),
+        }
+    }
+    Ok(())
 }
 
 pub struct Setup {
@@ -91,6 +346,14 @@ impl Setup {
     pub async fn initialize(self) {
         simple_logger::init().ok();
         let state = AppState::new().await.expect("cant start the server state");
+        if state.setting.read().await.watch {
+            let state = state.clone();
+            tokio::spawn(async move {
+                if let Err(err) = watch(state).await {
+                    log::error!("cant watch the content: {err:?}");
+                }
+            });
+        }
         let app = self.route.with_state(state.clone()).fallback_service(
             ServeDir::new("./public").not_found_service(Handler::with_state(error, state.clone())),
         );
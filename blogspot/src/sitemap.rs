@@ -0,0 +1,184 @@
+use crate::setup::{AppState, Setting};
+use markdown::{IndexMap, Language, PostDate};
+use serde::Deserialize;
+use std::fmt::Write;
+
+/// robots.txt rule, by default allow everything except the oauth flow and admin page
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Robots {
+    pub user_agent: String,
+    pub allow: Vec<String>,
+    pub disallow: Vec<String>,
+}
+
+impl Default for Robots {
+    fn default() -> Self {
+        Self {
+            user_agent: "*".into(),
+            allow: vec!["/".into()],
+            disallow: vec!["/oauth".into(), "/callback".into(), "/admin".into()],
+        }
+    }
+}
+
+/// single `<url>` entry, `alternates` is the same page on other language
+struct Url {
+    path: String,
+    lastmod: Option<PostDate>,
+    alternates: Vec<(Language, String)>,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// the same page on each of the languages, all of them point to each other as alternate
+fn paired(
+    languages: &[Language],
+    path: impl Fn(&Language) -> String,
+    lastmod: impl Fn(&Language) -> Option<PostDate>,
+) -> Vec<Url> {
+    let alternates = languages
+        .iter()
+        .map(|l| (l.clone(), path(l)))
+        .collect::<Vec<_>>();
+    alternates
+        .iter()
+        .map(|(l, p)| Url {
+            path: p.to_owned(),
+            lastmod: lastmod(l),
+            alternates: alternates.clone(),
+        })
+        .collect()
+}
+
+/// every page reachable from `routes::reg` for every language
+pub async fn sitemap(app: &AppState) -> String {
+    let languages = app.markdown.languages().await;
+    let mut urls = paired(&languages, |l| format!("/{l}"), |_| None);
+    urls.extend(paired(&languages, |l| format!("/{l}/portfolio"), |_| None));
+    urls.extend(paired(&languages, |l| format!("/{l}/series"), |_| None));
+    for post in app.markdown.post_types().await {
+        let mut lists = Vec::new();
+        for language in languages.iter().cloned() {
+            let pages = app
+                .markdown
+                .list(language.clone(), post.clone(), 1, false)
+                .await
+                .map_or(Vec::new(), |x| x.pagination.end);
+            let posts = app.markdown.all(language.clone(), post.clone()).await;
+            lists.push((language, pages.len(), posts));
+        }
+        // the newest post as the list page lastmod, the collection may not sorted by date
+        let newest = |language: &Language| {
+            lists
+                .iter()
+                .find(|(l, _, _)| l == language)
+                .and_then(|(_, _, posts)| posts.values().map(|m| m.updated.unwrap_or(m.date)).max())
+        };
+        urls.extend(paired(&languages, |l| format!("/{l}/{post}"), newest));
+        urls.extend(paired(&languages, |l| format!("/{l}/{post}/tags"), newest));
+        urls.extend(paired(
+            &languages,
+            |l| format!("/{l}/{post}/archive"),
+            newest,
+        ));
+        let most = lists.iter().map(|(_, pages, _)| *pages).max().unwrap_or(0);
+        for page in 2..=most {
+            let languages = lists
+                .iter()
+                .filter(|(_, pages, _)| *pages >= page)
+                .map(|(l, _, _)| l.clone())
+                .collect::<Vec<_>>();
+            urls.extend(paired(
+                &languages,
+                |l| format!("/{l}/{post}/{page}"),
+                newest,
+            ));
+        }
+        // the translation linked by `translation_key` can use other slug on each language
+        let mut keys = IndexMap::<String, Vec<(Language, &String, PostDate)>>::new();
+        for (language, _, posts) in &lists {
+            for (slug, meta) in posts {
+                let key = meta
+                    .translation_key
+                    .clone()
+                    .unwrap_or_else(|| slug.to_owned());
+                let date = meta.updated.unwrap_or(meta.date);
+                keys.entry(key)
+                    .or_default()
+                    .push((language.clone(), slug, date));
+            }
+        }
+        for found in keys.values() {
+            let languages = found.iter().map(|(l, _, _)| l.clone()).collect::<Vec<_>>();
+            let find = |language: &Language| found.iter().find(|(l, _, _)| l == language);
+            urls.extend(paired(
+                &languages,
+                |l| format!("/{l}/{post}/{}", find(l).map_or("", |x| x.1.as_str())),
+                |l| find(l).map(|x| x.2),
+            ));
+        }
+    }
+
+    let base = app.setting.read().await.base_url.to_owned();
+    let base = base.trim_end_matches('/');
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" \
+         xmlns:xhtml=\"http://www.w3.org/1999/xhtml\">\n",
+    );
+    for url in urls {
+        xml.push_str("  <url>\n");
+        writeln!(
+            xml,
+            "    <loc>{}</loc>",
+            escape(&format!("{base}{}", url.path))
+        )
+        .ok();
+        if let Some(lastmod) = url.lastmod {
+            writeln!(
+                xml,
+                "    <lastmod>{}</lastmod>",
+                escape(&lastmod.to_string())
+            )
+            .ok();
+        }
+        if url.alternates.len() > 1 {
+            for (language, path) in &url.alternates {
+                writeln!(
+                    xml,
+                    "    <xhtml:link rel=\"alternate\" hreflang=\"{language}\" href=\"{}\"/>",
+                    escape(&format!("{base}{path}"))
+                )
+                .ok();
+            }
+        }
+        xml.push_str("  </url>\n");
+    }
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+pub fn robots(setting: &Setting) -> String {
+    let robots = &setting.robots;
+    let mut txt = format!("User-agent: {}\n", robots.user_agent);
+    for path in &robots.allow {
+        writeln!(txt, "Allow: {path}").ok();
+    }
+    for path in &robots.disallow {
+        writeln!(txt, "Disallow: {path}").ok();
+    }
+    writeln!(
+        txt,
+        "\nSitemap: {}/sitemap.xml",
+        setting.base_url.trim_end_matches('/')
+    )
+    .ok();
+    txt
+}
@@ -7,8 +7,9 @@ use syn::Fields;
 /// need attribute location to get absolute location
 /// for example `#[location = "pages/dashboard"]`
 /// so on each variant like `Self::Intro`, it will be path
-/// `$template_path/pages/dashboard/Intro.html`
-/// ```rust
+/// `$template_path/pages/dashboard/Intro.html`, or use `#[location_field = "template"]`
+/// to take it from the `template` field of the variant
+/// ```ignore
 /// #[derive(PageRender)]
 /// pub enum Pages {
 ///     #[location = "pages/404.html"]
@@ -18,7 +19,7 @@ use syn::Fields;
 ///     Post { post: String },
 /// }
 /// ````
-#[proc_macro_derive(PageRender, attributes(location, error_page))]
+#[proc_macro_derive(PageRender, attributes(location, location_field, error_page))]
 pub fn page_render(input: TokenStream) -> TokenStream {
     let enum_ = syn::parse_macro_input!(input as syn::DeriveInput);
     match process_input(enum_) {
@@ -31,6 +32,8 @@ pub fn page_render(input: TokenStream) -> TokenStream {
 #[darling(allow_unknown_fields, default)]
 struct RenderAttr {
     location: String,
+    /// name of the field that hold the location, for page where its decided at runtime
+    location_field: Option<String>,
     error_page: Option<()>,
 }
 
@@ -79,11 +82,17 @@ fn process_input(inp: syn::DeriveInput) -> Result<TokenStream> {
                         Self::#ident => #location.into()
                     }
                 }
-                Fields::Named(_) => {
-                    quote! {
-                        Self::#ident {..} => #location.into()
+                Fields::Named(_) => match &variant_attr.location_field {
+                    Some(field) => {
+                        let field = syn::Ident::new(field, ident.span());
+                        quote! {
+                            Self::#ident { #field, .. } => #field.to_string()
+                        }
                     }
-                }
+                    None => quote! {
+                        Self::#ident {..} => #location.into()
+                    },
+                },
                 _ => panic!("tuple methode is not supported, its need name values like struct"),
             }
         });
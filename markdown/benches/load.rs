@@ -0,0 +1,66 @@
+//! loading benchmark on generated corpus, run with `cargo bench -p markdown`
+//! the corpus size can be changed with `BENCH_POSTS` env (default 3000 post)
+use markdown::{Config, Markdown};
+use std::{path::Path, time::Instant};
+
+fn post(n: usize) -> String {
+    let paragraph = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. Sed do eiusmod \
+                     tempor incididunt ut labore et dolore magna aliqua. Proin nibh nisl \
+                     condimentum id venenatis a condimentum vitae.\n\n";
+    let code =
+        "```rust\nfn main() {\n    let x = vec![1, 2, 3];\n    println!(\"{x:?}\");\n}\n```\n\n";
+    let mut body = String::new();
+    for section in 0..6 {
+        body.push_str(&format!("## Section {section}\n\n{paragraph}{paragraph}"));
+        body.push_str(&format!("### Detail {section}\n\n{paragraph}{code}"));
+    }
+    format!(
+        "---\ntitle: Generated post {n}\ndate: 2024-01-{:02}\nimage: /default.png\n\
+         description: generated post for benchmark\ntags:\n  - bench\n  - tag{}\n---\n{body}",
+        n % 28 + 1,
+        n % 10
+    )
+}
+
+fn generate(root: &Path, total: usize) {
+    let dirs = ["en/blog", "en/project", "id/blog", "id/project"];
+    for dir in dirs {
+        std::fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    for n in 0..total {
+        let dir = root.join(dirs[n % dirs.len()]);
+        std::fs::write(dir.join(format!("post{n}.md")), post(n)).unwrap();
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let total = std::env::var("BENCH_POSTS")
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .unwrap_or(3000);
+    let root = std::env::temp_dir().join("markdown-bench-corpus");
+    std::fs::remove_dir_all(&root).ok();
+    generate(&root, total);
+    println!("generated {total} post on {}", root.display());
+
+    for concurrency in [1, 0] {
+        let config = Config {
+            markdown_dir: root.clone(),
+            load_concurrency: concurrency,
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let markdown = Markdown::new(config).await.unwrap();
+        let load = start.elapsed();
+        let start = Instant::now();
+        markdown.reload().await.unwrap();
+        let reload = start.elapsed();
+        let label = match concurrency {
+            0 => "concurrent (cpu count)".to_owned(),
+            n => format!("concurrency {n}"),
+        };
+        println!("{label:>24}: load {load:>10.2?}  unchanged reload {reload:>10.2?}");
+    }
+    std::fs::remove_dir_all(&root).ok();
+}
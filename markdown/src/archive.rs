@@ -0,0 +1,52 @@
+use crate::PostDate;
+use chrono::Month;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// how many post on the month
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ArchiveMonth {
+    /// 1 for january
+    pub month: u32,
+    /// english name ex. "January"
+    pub name: String,
+    pub count: usize,
+}
+
+/// how many post on the year along with each month that have post
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ArchiveYear {
+    pub year: i32,
+    pub count: usize,
+    /// newest first
+    pub months: Vec<ArchiveMonth>,
+}
+
+/// post count of every year and month, newest first
+pub(crate) fn archive(dates: impl IntoIterator<Item = PostDate>) -> Vec<ArchiveYear> {
+    let mut counts = BTreeMap::<i32, BTreeMap<u32, usize>>::new();
+    for date in dates {
+        let months = counts.entry(date.year()).or_default();
+        *months.entry(date.month()).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .rev()
+        .map(|(year, months)| ArchiveYear {
+            year,
+            count: months.values().sum(),
+            months: months
+                .into_iter()
+                .rev()
+                .map(|(month, count)| ArchiveMonth {
+                    month,
+                    name: u8::try_from(month)
+                        .ok()
+                        .and_then(|x| Month::try_from(x).ok())
+                        .map_or_else(String::new, |x| x.name().to_owned()),
+                    count,
+                })
+                .collect(),
+        })
+        .collect()
+}
@@ -0,0 +1,156 @@
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::Display;
+
+/// with timezone, ex. "2024-01-05T10:30:00+07:00" or "2024-01-05 10:30 +0700"
+const ZONED: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%:z",
+    "%Y-%m-%d %H:%M:%S%:z",
+    "%Y-%m-%d %H:%M:%S %z",
+    "%Y-%m-%d %H:%M %z",
+];
+/// without timezone, treated as UTC
+const NAIVE: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// post date from the front matter, the time and timezone are optional so "2024-01-05"
+/// is midnight UTC
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PostDate {
+    datetime: DateTime<FixedOffset>,
+    has_time: bool,
+}
+
+impl PostDate {
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let zoned = DateTime::parse_from_rfc3339(text)
+            .ok()
+            .or_else(|| {
+                ZONED
+                    .iter()
+                    .find_map(|f| DateTime::parse_from_str(text, f).ok())
+            })
+            .map(|datetime| (datetime, true));
+        let naive = || {
+            NAIVE
+                .iter()
+                .find_map(|f| NaiveDateTime::parse_from_str(text, f).ok())
+                .map(|x| (x, true))
+                .or_else(|| {
+                    let date = NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?;
+                    Some((date.and_hms_opt(0, 0, 0)?, false))
+                })
+                .map(|(x, has_time)| (x.and_utc().fixed_offset(), has_time))
+        };
+        let (datetime, has_time) = zoned.or_else(naive)?;
+        Some(Self { datetime, has_time })
+    }
+    /// `false` when only the date written
+    pub fn has_time(&self) -> bool {
+        self.has_time
+    }
+    pub fn datetime(&self) -> DateTime<FixedOffset> {
+        self.datetime
+    }
+    pub fn year(&self) -> i32 {
+        self.datetime.year()
+    }
+    pub fn month(&self) -> u32 {
+        self.datetime.month()
+    }
+}
+
+/// "Y-M-D" when written without time, RFC 3339 otherwise
+impl Display for PostDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.has_time {
+            f.write_str(&self.datetime.to_rfc3339())
+        } else {
+            write!(f, "{}", self.datetime.format("%Y-%m-%d"))
+        }
+    }
+}
+
+impl Serialize for PostDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for PostDate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Self::parse(&text).ok_or_else(|| {
+            serde::de::Error::custom(format!(
+                "`{text}` is not a date, expected Y-M-D with optional time and timezone \
+                 ex. 2024-01-05 or 2024-01-05T10:30:00+07:00"
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rfc3339(text: &str) -> String {
+        PostDate::parse(text).unwrap().datetime().to_rfc3339()
+    }
+
+    #[test]
+    fn date_only() {
+        let date = PostDate::parse("2024-01-05").unwrap();
+        assert!(!date.has_time());
+        assert_eq!(date.to_string(), "2024-01-05");
+        assert_eq!(rfc3339(" 2024-01-05 "), "2024-01-05T00:00:00+00:00");
+    }
+
+    #[test]
+    fn with_timezone() {
+        let expected = "2024-01-05T10:30:00+07:00";
+        assert_eq!(rfc3339("2024-01-05T10:30:00+07:00"), expected);
+        assert_eq!(rfc3339("2024-01-05 10:30:00+07:00"), expected);
+        assert_eq!(rfc3339("2024-01-05 10:30:00 +0700"), expected);
+        assert_eq!(rfc3339("2024-01-05 10:30 +0700"), expected);
+        assert_eq!(rfc3339("2024-01-05T03:30:00Z"), "2024-01-05T03:30:00+00:00");
+        assert!(PostDate::parse("2024-01-05T10:30:00+07:00")
+            .unwrap()
+            .has_time());
+    }
+
+    #[test]
+    fn without_timezone_is_utc() {
+        let expected = "2024-01-05T10:30:00+00:00";
+        assert_eq!(rfc3339("2024-01-05T10:30:00"), expected);
+        assert_eq!(rfc3339("2024-01-05 10:30:00"), expected);
+        assert_eq!(rfc3339("2024-01-05T10:30"), expected);
+        assert_eq!(rfc3339("2024-01-05 10:30"), expected);
+    }
+
+    #[test]
+    fn rejected() {
+        for text in [
+            "",
+            "2024-13-45",
+            "2024-02-30",
+            "05-01-2024",
+            "2024/01/05",
+            "2024-01-05 25:00",
+            "tomorrow",
+        ] {
+            assert_eq!(PostDate::parse(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn ordered_by_moment() {
+        let early = PostDate::parse("2024-01-05 10:00 +0700").unwrap();
+        let late = PostDate::parse("2024-01-05 04:00").unwrap();
+        assert!(early < late);
+    }
+}
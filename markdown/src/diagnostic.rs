@@ -0,0 +1,71 @@
+use crate::Error;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// problem found while loading a post, collected instead of failing on lenient mode
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// `None` for error that not caused by single file
+    pub fn new(err: &Error) -> Option<Self> {
+        let (path, line) = match err {
+            Error::ReadError { path, .. } => (path, None),
+            Error::MetaError { path, line, .. } => (path, *line),
+            Error::FileError | Error::PageNotFound { .. } => return None,
+        };
+        Some(Self {
+            path: path.to_owned(),
+            line,
+            message: err.to_string(),
+        })
+    }
+}
+
+/// "path:line" like the compiler so editor can jump to it
+pub(crate) fn location(path: &Path, line: Option<usize>) -> String {
+    match line {
+        Some(line) => format!("{}:{line}", path.display()),
+        None => path.display().to_string(),
+    }
+}
+
+/// where the front matter written on the file
+pub(crate) struct FrontMatter<'a> {
+    text: &'a str,
+    /// line number of the first front matter line on the file
+    start: usize,
+}
+
+impl<'a> FrontMatter<'a> {
+    pub fn new(file: &str, text: &'a str) -> Self {
+        let start = match text.lines().next().and_then(|first| file.find(first)) {
+            Some(index) => file[..index].matches('\n').count() + 1,
+            None => 1,
+        };
+        Self { text, start }
+    }
+    /// line of the top level field, ex. "tags" for "tags[0]"
+    pub fn field_line(&self, field: &str) -> Option<usize> {
+        let key = field.split(['[', '.']).next()?;
+        self.text
+            .lines()
+            .position(|line| {
+                line.strip_prefix(key)
+                    .is_some_and(|rest| rest.trim_start().starts_with(':'))
+            })
+            .map(|index| self.start + index)
+    }
+    /// line and reason of the yaml syntax error, gray_matter only give empty data for it
+    pub fn syntax_error(&self) -> Option<(usize, String)> {
+        let err = yaml_rust::YamlLoader::load_from_str(self.text).err()?;
+        // the message end with the line relative to the front matter, which is confusing
+        let message = err.to_string();
+        let reason = message.split(" at line").next().unwrap_or_default();
+        Some((self.start + err.marker().line() - 1, reason.to_owned()))
+    }
+}
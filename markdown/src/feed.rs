@@ -0,0 +1,187 @@
+use crate::PostData;
+use atom_syndication::{Content, Entry, Feed, Link, Text};
+use chrono::Utc;
+use rss::{Channel, Guid, Item};
+
+/// how many newest post included on the feed
+const FEED_LIMIT: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeedFormat {
+    /// rss 2.0 served as `feed.xml`
+    Rss,
+    /// atom 1.0 served as `atom.xml`
+    Atom,
+}
+
+impl FeedFormat {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Self::Rss => "feed.xml",
+            Self::Atom => "atom.xml",
+        }
+    }
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Rss => "application/rss+xml; charset=utf-8",
+            Self::Atom => "application/atom+xml; charset=utf-8",
+        }
+    }
+}
+
+/// the site the feed belong to, feed reader need absolute url so `base_url` must be
+/// the public url like "https://example.com"
+#[derive(Clone, Copy, Debug)]
+pub struct Site<'a> {
+    pub base_url: &'a str,
+    pub name: &'a str,
+}
+
+/// everything needed to write a feed, `path` is where the post live ex. "/en/blog",
+/// `feed_path` is where the feed itself served ex. "/en/blog/tag/rust" and `page_path`
+/// is the html list of the same post ex. "/en/blog/tag/rust/1"
+pub(crate) struct FeedData<'a> {
+    pub title: String,
+    pub language: String,
+    pub path: String,
+    pub feed_path: String,
+    pub page_path: String,
+    pub posts: Vec<(&'a String, &'a PostData)>,
+}
+
+/// make the relative link on the content absolute, feed reader cant resolve them
+fn absolute_content(base: &str, post: &PostData) -> String {
+    let content = post
+        .content
+        .replace("src=\"/", &format!("src=\"{base}/"))
+        .replace("href=\"/", &format!("href=\"{base}/"));
+    if post.metadata.image.is_empty() {
+        return content;
+    }
+    let image = absolute(base, &post.metadata.image);
+    format!("<p><img src=\"{image}\" alt=\"\" /></p>\n{content}")
+}
+
+fn absolute(base: &str, url: &str) -> String {
+    if url.starts_with('/') {
+        format!("{base}{url}")
+    } else {
+        url.to_owned()
+    }
+}
+
+impl FeedData<'_> {
+    pub fn render(mut self, format: FeedFormat, site: Site) -> String {
+        self.posts.truncate(FEED_LIMIT);
+        let base = site.base_url.trim_end_matches('/');
+        match format {
+            FeedFormat::Rss => self.rss(base, site),
+            FeedFormat::Atom => self.atom(base, site),
+        }
+    }
+
+    fn rss(self, base: &str, site: Site) -> String {
+        let items = self
+            .posts
+            .iter()
+            .map(|(slug, post)| {
+                let link = format!("{base}{}/{slug}", self.path);
+                Item {
+                    title: Some(post.metadata.title.to_owned()),
+                    link: Some(link.clone()),
+                    description: Some(post.metadata.excerpt.to_owned()),
+                    guid: Some(Guid {
+                        value: link,
+                        permalink: true,
+                    }),
+                    pub_date: Some(post.metadata.date.datetime().to_rfc2822()),
+                    categories: post
+                        .metadata
+                        .tags
+                        .iter()
+                        .map(|tag| rss::Category {
+                            name: tag.to_owned(),
+                            domain: None,
+                        })
+                        .collect(),
+                    content: Some(absolute_content(base, post)),
+                    ..Default::default()
+                }
+            })
+            .collect();
+        Channel {
+            title: format!("{} | {}", self.title, site.name),
+            link: format!("{base}{}", self.page_path),
+            description: format!("{} from {}", self.title, site.name),
+            language: Some(self.language),
+            last_build_date: Some(Utc::now().to_rfc2822()),
+            items,
+            ..Default::default()
+        }
+        .to_string()
+    }
+
+    fn atom(self, base: &str, site: Site) -> String {
+        let entries = self
+            .posts
+            .iter()
+            .map(|(slug, post)| {
+                let link = format!("{base}{}/{slug}", self.path);
+                let date = post.metadata.date;
+                Entry {
+                    title: Text::plain(post.metadata.title.to_owned()),
+                    id: link.clone(),
+                    updated: post.metadata.updated.unwrap_or(date).datetime(),
+                    published: Some(date.datetime()),
+                    links: vec![Link {
+                        href: link,
+                        rel: "alternate".into(),
+                        ..Default::default()
+                    }],
+                    categories: post
+                        .metadata
+                        .tags
+                        .iter()
+                        .map(|tag| atom_syndication::Category {
+                            term: tag.to_owned(),
+                            ..Default::default()
+                        })
+                        .collect(),
+                    summary: Some(Text::plain(post.metadata.excerpt.to_owned())),
+                    content: Some(Content {
+                        value: Some(absolute_content(base, post)),
+                        content_type: Some("html".into()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }
+            })
+            .collect::<Vec<_>>();
+        let feed_url = format!("{base}{}/{}", self.feed_path, FeedFormat::Atom.file_name());
+        Feed {
+            title: Text::plain(format!("{} | {}", self.title, site.name)),
+            id: feed_url.clone(),
+            updated: entries
+                .iter()
+                .map(|e| e.updated)
+                .max()
+                .unwrap_or_else(|| Utc::now().fixed_offset()),
+            links: vec![
+                Link {
+                    href: feed_url,
+                    rel: "self".into(),
+                    ..Default::default()
+                },
+                Link {
+                    href: format!("{base}{}", self.page_path),
+                    rel: "alternate".into(),
+                    ..Default::default()
+                },
+            ],
+            lang: Some(self.language),
+            entries,
+            ..Default::default()
+        }
+        .to_string()
+    }
+}
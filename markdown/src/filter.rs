@@ -0,0 +1,96 @@
+use crate::{tags, MetaData, PostDate};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// how the wanted tags combined
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMode {
+    /// the post must have every tag
+    #[default]
+    All,
+    /// the post must have at least one of the tag
+    Any,
+}
+
+/// which post shown on the list
+#[derive(Serialize, Clone, Debug, PartialEq, Default)]
+pub struct PostFilter {
+    pub tags: Vec<String>,
+    pub mode: TagMode,
+    /// the post must have none of these
+    pub exclude: Vec<String>,
+    /// on or after this date, from the start of the day when its written without time
+    pub from: Option<PostDate>,
+    /// on or before this date, the whole day when its written without time
+    pub to: Option<PostDate>,
+}
+
+impl PostFilter {
+    /// from comma separated list like "rust,web,-draft", the one starting with `-` excluded
+    pub fn new(tags: &str, mode: TagMode) -> Self {
+        let mut filter = Self {
+            mode,
+            ..Self::default()
+        };
+        for tag in tags.split(',').map(str::trim).filter(|x| !x.is_empty()) {
+            match tag.strip_prefix('-') {
+                Some(tag) => filter.exclude.push(tag.to_owned()),
+                None => filter.tags.push(tag.to_owned()),
+            }
+        }
+        filter
+    }
+    pub fn with_dates(self, from: Option<PostDate>, to: Option<PostDate>) -> Self {
+        Self { from, to, ..self }
+    }
+    /// the whole year, or only the month if given. `None` for month outside 1 to 12
+    pub fn period(year: i32, month: Option<u32>) -> Option<Self> {
+        let (start, end) = match month {
+            Some(month) => {
+                let start = NaiveDate::from_ymd_opt(year, month, 1)?;
+                let next = match month {
+                    12 => NaiveDate::from_ymd_opt(year + 1, 1, 1)?,
+                    _ => NaiveDate::from_ymd_opt(year, month + 1, 1)?,
+                };
+                (start, next.pred_opt()?)
+            }
+            None => (
+                NaiveDate::from_ymd_opt(year, 1, 1)?,
+                NaiveDate::from_ymd_opt(year, 12, 31)?,
+            ),
+        };
+        let date = |x: NaiveDate| PostDate::parse(&x.format("%Y-%m-%d").to_string());
+        Some(Self::default().with_dates(date(start), date(end)))
+    }
+    /// nothing filtered
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+    /// written the same way as the post tag so "Rust" match "rust"
+    pub(crate) fn normalized(mut self, aliases: &HashMap<String, String>) -> Self {
+        for tag in self.tags.iter_mut().chain(self.exclude.iter_mut()) {
+            *tag = tags::normalize(tag, aliases);
+        }
+        self
+    }
+    pub(crate) fn matches(&self, metadata: &MetaData) -> bool {
+        let has = |tag: &String| metadata.tags.contains(tag);
+        let tagged = match self.mode {
+            _ if self.tags.is_empty() => true,
+            TagMode::All => self.tags.iter().all(has),
+            TagMode::Any => self.tags.iter().any(has),
+        };
+        let date = metadata.date.datetime();
+        let after = self.from.is_none_or(|from| match from.has_time() {
+            true => date >= from.datetime(),
+            false => date.date_naive() >= from.datetime().date_naive(),
+        });
+        let before = self.to.is_none_or(|to| match to.has_time() {
+            true => date <= to.datetime(),
+            false => date.date_naive() <= to.datetime().date_naive(),
+        });
+        tagged && !self.exclude.iter().any(has) && after && before
+    }
+}
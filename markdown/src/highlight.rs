@@ -0,0 +1,101 @@
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use syntect::{
+    highlighting::ThemeSet,
+    html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+/// where the fenced code block get coloured
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Highlight {
+    /// highlighted on server when the markdown loaded, need the css from [`theme_css`]
+    #[default]
+    Syntect,
+    /// left untouched for `public/prism.js` to colour it on the browser
+    Prism,
+}
+
+/// prefixed so it wont collide with tailwind class
+const STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn themes() -> &'static ThemeSet {
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+    THEMES.get_or_init(ThemeSet::load_defaults)
+}
+
+/// the css for highlighted code block, `None` if the theme isnt one of syntect default themes
+pub fn theme_css(theme: &str) -> Option<String> {
+    css_for_theme_with_class_style(themes().themes.get(theme)?, STYLE).ok()
+}
+
+/// highlight the code into classed html, unknown language fallback to plain text
+fn highlight_code(code: &str, lang: &str) -> Option<String> {
+    let syntaxes = syntaxes();
+    let syntax = syntaxes
+        .find_syntax_by_token(lang)
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, syntaxes, STYLE);
+    for line in LinesWithEndings::from(code) {
+        generator
+            .parse_html_for_line_which_includes_newline(line)
+            .ok()?;
+    }
+    Some(format!(
+        "<pre class=\"hl-code\"><code class=\"language-{lang}\">{}</code></pre>\n",
+        generator.finalize()
+    ))
+}
+
+/// replace every code block with the highlighted html, the block is left as is when
+/// syntect fail so it still rendered (uncoloured) by pulldown-cmark
+pub fn highlight_events(events: Vec<Event>) -> Vec<Event> {
+    let mut out = Vec::with_capacity(events.len());
+    let mut block: Option<(String, Vec<Event>)> = None;
+    for event in events {
+        match (&mut block, event) {
+            (None, Event::Start(Tag::CodeBlock(kind))) => {
+                let lang = match &kind {
+                    // info string like "rust,ignore" or "rust title=x" only need the first word
+                    CodeBlockKind::Fenced(info) => info
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .next()
+                        .unwrap_or_default()
+                        .chars()
+                        .filter(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '+' | '#'))
+                        .collect(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                block = Some((lang, vec![Event::Start(Tag::CodeBlock(kind))]));
+            }
+            (Some((lang, raw)), Event::End(TagEnd::CodeBlock)) => {
+                let code = raw
+                    .iter()
+                    .filter_map(|e| match e {
+                        Event::Text(t) => Some(t.as_ref()),
+                        _ => None,
+                    })
+                    .collect::<String>();
+                match highlight_code(&code, lang) {
+                    Some(html) => out.push(Event::Html(CowStr::from(html))),
+                    None => {
+                        out.append(raw);
+                        out.push(Event::End(TagEnd::CodeBlock));
+                    }
+                }
+                block = None;
+            }
+            (Some((_, raw)), event) => raw.push(event),
+            (None, event) => out.push(event),
+        }
+    }
+    out
+}
@@ -1,53 +1,297 @@
-use gray_matter::{engine::YAML, Matter};
+pub use archive::{ArchiveMonth, ArchiveYear};
+use chrono::{DateTime, Utc};
+pub use date::PostDate;
+pub use diagnostic::Diagnostic;
+use diagnostic::{location, FrontMatter};
+use feed::FeedData;
+pub use feed::{FeedFormat, Site};
+pub use filter::{PostFilter, TagMode};
+use gray_matter::{engine::YAML, Matter, Pod};
+pub use highlight::Highlight;
 pub use indexmap::IndexMap;
+use load::{Loaded, Sources};
+pub use pagination::Pagination;
 use pulldown_cmark::{Options, Parser};
+use related::RelatedIndex;
+pub use related::{Navigation, PostLink};
+use search::SearchIndex;
+pub use search::{SearchHit, SearchResult};
 use serde::{Deserialize, Serialize};
+use series::SeriesIndex;
+pub use series::{Series, SeriesMeta, SeriesPart};
 use std::{
     collections::HashMap,
-    ffi::OsStr,
-    ops::{AddAssign, Deref},
+    fmt::Display,
+    ops::Deref,
     path::{Path, PathBuf},
     sync::Arc,
 };
+pub use tags::TagCount;
 use thiserror::Error;
-use tokio::sync::RwLock;
+pub use toc::Heading;
+use tokio::sync::{Mutex, RwLock};
+use translation::TranslationIndex;
+pub use translation::{Translation, TranslationStatus};
+
+pub mod archive;
+pub mod date;
+pub mod diagnostic;
+pub mod feed;
+pub mod filter;
+pub mod highlight;
+mod load;
+pub mod pagination;
+mod reading;
+pub mod related;
+pub mod search;
+pub mod series;
+pub mod tags;
+pub mod toc;
+pub mod translation;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct MetaData {
     pub title: String,
-    pub date: String,
+    pub date: PostDate,
+    /// when the post last revised, must not be before `date`
+    #[serde(default)]
+    pub updated: Option<PostDate>,
+    /// shown on the list and feed, the start of the content used when its empty
+    #[serde(default)]
     pub description: String,
     pub image: String,
     pub tags: Vec<String>,
+    /// link the post to its translation on other language that use different slug
+    #[serde(default)]
+    pub translation_key: Option<String>,
+    /// the language this post actually written on when its shown as fallback of missing
+    /// translation, `None` if its the requested language
+    #[serde(skip_deserializing)]
+    pub fallback_language: Option<Language>,
+    /// only shown to the admin
+    #[serde(default)]
+    pub draft: bool,
+    /// hidden until this time
+    #[serde(default)]
+    pub publish_at: Option<PostDate>,
+    /// hidden from this time, must be after `publish_at`
+    #[serde(default)]
+    pub expires_at: Option<PostDate>,
+    /// part of multi post series, linked on every part
+    #[serde(default)]
+    pub series: Option<SeriesMeta>,
+    /// shown first on the first page of the list
+    #[serde(default)]
+    pub pinned: bool,
+    /// shown on the intro page when its on the `featured_collection`
+    #[serde(default)]
+    pub featured: bool,
+    /// visibility at the time of the request, so the admin know what the visitor see
+    #[serde(skip_deserializing)]
+    pub status: PostStatus,
+    /// counted from the content, every cjk character count as a word
+    #[serde(skip_deserializing)]
+    pub word_count: usize,
+    /// estimated minutes to read from the language `words_per_minute`
+    #[serde(skip_deserializing)]
+    pub reading_time: usize,
+    /// the description, or the start of the content when its empty
+    #[serde(skip_deserializing)]
+    pub excerpt: String,
+}
+
+/// whether the visitor can see the post, decided on each request so the scheduled post
+/// show up on time without reload
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PostStatus {
+    #[default]
+    Published,
+    Draft,
+    Scheduled,
+    Expired,
+}
+
+impl MetaData {
+    /// visibility at the given time, draft win over the schedule
+    pub fn status_at(&self, now: DateTime<Utc>) -> PostStatus {
+        if self.draft {
+            PostStatus::Draft
+        } else if self.publish_at.is_some_and(|at| at.datetime() > now) {
+            PostStatus::Scheduled
+        } else if self.expires_at.is_some_and(|at| at.datetime() <= now) {
+            PostStatus::Expired
+        } else {
+            PostStatus::Published
+        }
+    }
+    pub fn is_public(&self, now: DateTime<Utc>) -> bool {
+        self.status_at(now) == PostStatus::Published
+    }
+    /// copy for the page with the fallback language and the status at `now` filled
+    pub(crate) fn shown(&self, fallback_language: Option<Language>, now: DateTime<Utc>) -> Self {
+        Self {
+            fallback_language,
+            status: self.status_at(now),
+            ..self.clone()
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct PostData {
     pub metadata: MetaData,
     pub content: String,
+    /// table of content
+    pub toc: Vec<Heading>,
+    /// plain text of the content for search
+    #[serde(skip)]
+    pub text: String,
 }
 
-#[derive(Hash, PartialEq, Eq, Debug, Clone, Copy, Serialize)]
-pub enum PostType {
-    #[serde(rename = "project")]
-    Project,
-    #[serde(rename = "blog")]
-    Blog,
+/// collection of post ex. "blog" as its url prefix, the available one come from
+/// `Config::collections`
+#[derive(Hash, PartialEq, Eq, PartialOrd, Ord, Debug, Clone)]
+pub struct PostType(Arc<str>);
+
+/// language code of the post ex. "en", the available one come from `Config::languages`
+#[derive(Hash, PartialEq, Eq, PartialOrd, Ord, Debug, Clone)]
+pub struct Language(Arc<str>);
+
+impl PostType {
+    pub fn new(prefix: impl AsRef<str>) -> Self {
+        Self(prefix.as_ref().into())
+    }
+    pub fn prefix(&self) -> &str {
+        &self.0
+    }
 }
 
-#[derive(Hash, PartialEq, Eq, Debug, Clone, Copy, Serialize)]
-pub enum Language {
-    #[serde(rename = "en")]
-    Eng,
-    #[serde(rename = "id")]
-    Idn,
+impl Serialize for PostType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
 }
 
-/// pagination struct
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct Pagination {
-    pub current: usize,
-    pub end: Vec<usize>,
+impl Language {
+    pub fn new(code: impl AsRef<str>) -> Self {
+        Self(code.as_ref().into())
+    }
+    pub fn code(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Serialize for Language {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl Display for PostType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// single language of the site as written on Setting.toml `[[languages]]`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LanguageConfig {
+    /// used on the url and as the markdown folder name ex. "en"
+    pub code: String,
+    /// shown on the language switcher ex. "English (US)"
+    pub name: String,
+    /// image url (starting with `/` or `http`) or short text like emoji shown beside the name
+    #[serde(default)]
+    pub flag: String,
+    /// written right to left like arabic
+    #[serde(default)]
+    pub rtl: bool,
+    /// language code tried in order when the post is not translated yet, the default
+    /// language always tried last
+    #[serde(default)]
+    pub fallback: Vec<String>,
+    /// reading speed for the reading time, for cjk language its character per minute
+    #[serde(default = "default_words_per_minute")]
+    pub words_per_minute: usize,
+}
+
+fn default_words_per_minute() -> usize {
+    200
+}
+
+/// how the post of a collection ordered on the list
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// newest date first
+    #[default]
+    Newest,
+    Oldest,
+    /// alphabetically by title
+    Title,
+}
+
+/// single collection of post as written on Setting.toml `[[collections]]`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CollectionConfig {
+    /// used on the url ex. "blog" for `/en/blog`
+    pub prefix: String,
+    /// shown as the feed title, the prefix if empty
+    #[serde(default)]
+    pub name: String,
+    /// folder under `{markdown_dir}/{language}`, the prefix if empty
+    #[serde(default)]
+    pub dir: String,
+    /// how many post on each list page
+    #[serde(default = "default_page_size")]
+    pub page_size: usize,
+    /// template of the list page relative to the templates folder
+    #[serde(default = "default_list_template")]
+    pub template: String,
+    #[serde(default)]
+    pub sort: SortOrder,
+}
+
+fn default_page_size() -> usize {
+    6
+}
+
+fn default_list_template() -> String {
+    "pages/blog_list.html".into()
+}
+
+impl CollectionConfig {
+    fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.into(),
+            name: String::new(),
+            dir: String::new(),
+            page_size: default_page_size(),
+            template: default_list_template(),
+            sort: SortOrder::default(),
+        }
+    }
+    pub fn name(&self) -> &str {
+        if self.name.is_empty() {
+            &self.prefix
+        } else {
+            &self.name
+        }
+    }
+    pub fn dir(&self) -> &str {
+        if self.dir.is_empty() {
+            &self.prefix
+        } else {
+            &self.dir
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -60,168 +304,752 @@ pub struct PostList {
 pub enum Error {
     #[error("cant get access to filesystem or not found")]
     FileError,
-    #[error("invalid Yaml Meta format")]
-    MetaError,
+    #[error("cant read {}: {reason}", path.display())]
+    ReadError { path: PathBuf, reason: String },
+    #[error("invalid Yaml Meta on {}: `{field}` {reason}", location(path, *line))]
+    MetaError {
+        path: PathBuf,
+        line: Option<usize>,
+        field: String,
+        reason: String,
+    },
+    #[error("page {page} not found, there are {pages} pages")]
+    PageNotFound { page: usize, pages: usize },
+}
+
+/// markdown loading option, all of them have default so it can be left empty
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Config {
+    /// colour the code block on server or leave it to prism
+    pub highlight: Highlight,
+    /// syntect theme used for `/highlight.css` ex. "InspiredGitHub"
+    pub highlight_theme: String,
+    /// where the markdown live, as `{markdown_dir}/{language}/{post}/{slug}.md`
+    pub markdown_dir: PathBuf,
+    /// how many file loaded at the same time, 0 to follow the cpu count
+    pub load_concurrency: usize,
+    /// every language served, on the order shown on the language switcher
+    pub languages: Vec<LanguageConfig>,
+    /// code of the language `/` go to, the first one if empty or unknown
+    pub default_language: String,
+    /// every collection of post served on each language
+    pub collections: Vec<CollectionConfig>,
+    /// include the post that not translated yet (from the fallback language) on list page
+    pub list_untranslated: bool,
+    /// skip the broken post and collect the diagnostic instead of failing the whole load
+    pub lenient: bool,
+    /// how many search result per page
+    pub search_page_size: usize,
+    /// tag replaced on load ex. "rustlang" to "rust", compared after normalized
+    pub tag_aliases: HashMap<String, String>,
+    /// collection prefix the featured post on the intro page taken from
+    pub featured_collection: String,
+}
+
+impl Config {
+    /// every configured language in order
+    pub fn languages(&self) -> Vec<Language> {
+        self.languages
+            .iter()
+            .map(|l| Language::new(&l.code))
+            .collect()
+    }
+    /// every configured collection in order
+    pub fn post_types(&self) -> Vec<PostType> {
+        self.collections
+            .iter()
+            .map(|c| PostType::new(&c.prefix))
+            .collect()
+    }
+    pub fn collection(&self, post: &PostType) -> Option<&CollectionConfig> {
+        self.collections.iter().find(|c| c.prefix == post.prefix())
+    }
+    /// the language itself followed by where to look when the post is missing on it
+    pub fn fallback_chain(&self, language: &Language) -> Vec<Language> {
+        let mut chain = vec![language.clone()];
+        let fallback = self
+            .languages
+            .iter()
+            .find(|l| l.code == language.code())
+            .map(|l| l.fallback.iter().map(Language::new).collect::<Vec<_>>())
+            .unwrap_or_default();
+        for next in fallback.into_iter().chain([self.default_language()]) {
+            if !chain.contains(&next) {
+                chain.push(next);
+            }
+        }
+        chain
+    }
+    /// reading speed of the language the post written on, from its path
+    fn words_per_minute(&self, path: &Path) -> usize {
+        load::locate(path, self)
+            .and_then(|(language, _, _)| self.languages.iter().find(|l| l.code == language.code()))
+            .map_or_else(default_words_per_minute, |l| l.words_per_minute)
+    }
+    /// the language used when nothing else decided
+    pub fn default_language(&self) -> Language {
+        self.languages
+            .iter()
+            .find(|l| l.code == self.default_language)
+            .or(self.languages.first())
+            .map_or_else(
+                || Language::new(&self.default_language),
+                |l| Language::new(&l.code),
+            )
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            highlight: Highlight::default(),
+            highlight_theme: "base16-ocean.dark".into(),
+            markdown_dir: Path::new(".").join("pages").join("markdown"),
+            load_concurrency: 0,
+            languages: vec![
+                LanguageConfig {
+                    code: "en".into(),
+                    name: "English (US)".into(),
+                    flag: "/flags/en.svg".into(),
+                    rtl: false,
+                    fallback: Vec::new(),
+                    words_per_minute: default_words_per_minute(),
+                },
+                LanguageConfig {
+                    code: "id".into(),
+                    name: "Indonesia".into(),
+                    flag: "/flags/id.svg".into(),
+                    rtl: false,
+                    fallback: Vec::new(),
+                    words_per_minute: default_words_per_minute(),
+                },
+            ],
+            default_language: "en".into(),
+            collections: vec![
+                CollectionConfig::new("blog"),
+                CollectionConfig::new("project"),
+            ],
+            list_untranslated: false,
+            lenient: false,
+            search_page_size: 6,
+            tag_aliases: HashMap::new(),
+            featured_collection: "project".to_owned(),
+        }
+    }
 }
 
 pub type MyResult<T> = Result<T, Error>;
-type MarkdownData = HashMap<Language, HashMap<PostType, IndexMap<String, PostData>>>;
+pub(crate) type Posts = HashMap<Language, HashMap<PostType, IndexMap<String, Arc<PostData>>>>;
+
+/// loaded posts along with the index and config they built from, swapped together on reload
+#[derive(Debug, Default)]
+pub struct MarkdownData {
+    posts: Posts,
+    sources: Sources,
+    diagnostics: Vec<Diagnostic>,
+    search: SearchIndex,
+    translations: TranslationIndex,
+    related: RelatedIndex,
+    series: SeriesIndex,
+    config: Config,
+}
 
 #[derive(Clone, Debug)]
-pub struct Markdown(Arc<RwLock<MarkdownData>>);
+pub struct Markdown {
+    data: Arc<RwLock<MarkdownData>>,
+    /// so only one reload running at a time and none of them overwrite the other
+    reloading: Arc<Mutex<()>>,
+}
 
 impl Deref for Markdown {
     type Target = Arc<RwLock<MarkdownData>>;
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.data
     }
 }
 
-async fn parse_content(path: PathBuf) -> MyResult<PostData> {
-    if let Ok(text) = tokio::fs::read_to_string(path).await {
-        let matter = Matter::<YAML>::new();
-        let metadata = matter
-            .parse_with_struct::<MetaData>(&text)
-            .ok_or(Error::MetaError)?;
-        let parser = Parser::new_ext(&metadata.content, Options::ENABLE_HEADING_ATTRIBUTES);
-
-        // to do get Table of Content
-        let mut html = "".to_string();
-        pulldown_cmark::html::push_html(&mut html, parser);
-        return Ok(PostData {
-            metadata: metadata.data,
-            content: html,
-        });
-    }
-    Err(Error::FileError)
-}
-
-async fn parse_from_path(dir: PathBuf) -> MyResult<IndexMap<String, PostData>> {
-    let mut list = tokio::fs::read_dir(dir)
-        .await
-        .map_err(|_| Error::FileError)?;
-    let mut out = IndexMap::new();
-    while let Ok(Some(entry)) = list.next_entry().await {
-        let path = entry.path();
-        let extension = path.extension().unwrap_or_default();
-        if extension == OsStr::new("md") {
-            if let Some(name) = path.file_stem() {
-                out.insert(
-                    name.to_str().unwrap().to_owned(),
-                    parse_content(path).await?,
-                );
+fn parse_content(path: &Path, text: &str, config: &Config) -> MyResult<PostData> {
+    let matter = Matter::<YAML>::new().parse(text);
+    let front = FrontMatter::new(text, &matter.matter);
+    let invalid = |field: &str, line: Option<usize>, reason: String| Error::MetaError {
+        path: path.to_owned(),
+        line,
+        field: field.to_owned(),
+        reason,
+    };
+    let data = match matter.data {
+        Some(Pod::Null) | None => {
+            return Err(match front.syntax_error() {
+                Some((line, reason)) => invalid("front matter", Some(line), reason),
+                None => invalid("front matter", Some(1), "is missing".into()),
+            })
+        }
+        Some(data) => data,
+    };
+    // the path tell which field failed, its empty for missing field but the reason name it
+    let data: serde_json::Value = data.into();
+    let mut metadata: MetaData = serde_path_to_error::deserialize(data).map_err(|err| {
+        let (field, line) = match err.path().to_string() {
+            path if path == "." => ("front matter".to_owned(), None),
+            path => (path.clone(), front.field_line(&path)),
+        };
+        invalid(&field, line, err.into_inner().to_string())
+    })?;
+    if metadata
+        .updated
+        .is_some_and(|updated| updated < metadata.date)
+    {
+        let line = front.field_line("updated");
+        return Err(invalid("updated", line, "is before `date`".into()));
+    }
+    if metadata
+        .series
+        .as_ref()
+        .is_some_and(|series| series.name.trim().is_empty())
+    {
+        let line = front.field_line("series");
+        return Err(invalid("series.name", line, "is empty".into()));
+    }
+    if let Some((publish, expires)) = metadata.publish_at.zip(metadata.expires_at) {
+        if expires <= publish {
+            let line = front.field_line("expires_at");
+            return Err(invalid(
+                "expires_at",
+                line,
+                "is not after `publish_at`".into(),
+            ));
+        }
+    }
+    let mut events =
+        Parser::new_ext(&matter.content, Options::ENABLE_HEADING_ATTRIBUTES).collect::<Vec<_>>();
+    let toc = toc::anchor_headings(&mut events);
+    let text = search::plain_text(&events);
+    let mut tags = Vec::new();
+    for tag in &metadata.tags {
+        let tag = tags::normalize(tag, &config.tag_aliases);
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    metadata.tags = tags;
+    metadata.word_count = reading::word_count(&text);
+    metadata.reading_time =
+        reading::reading_time(metadata.word_count, config.words_per_minute(path));
+    metadata.excerpt = match metadata.description.trim() {
+        "" => reading::excerpt(&reading::paragraph_text(&events)),
+        description => description.to_owned(),
+    };
+    if config.highlight == Highlight::Syntect {
+        events = highlight::highlight_events(events);
+    }
+    let mut html = "".to_string();
+    pulldown_cmark::html::push_html(&mut html, events.into_iter());
+    Ok(PostData {
+        metadata,
+        content: html,
+        toc,
+        text,
+    })
+}
+
+impl MarkdownData {
+    fn new(loaded: Loaded, config: Config) -> Self {
+        Self {
+            search: SearchIndex::new(&loaded.posts),
+            translations: TranslationIndex::new(&loaded.posts),
+            related: RelatedIndex::new(&loaded.posts),
+            series: SeriesIndex::new(&loaded.posts),
+            posts: loaded.posts,
+            sources: loaded.sources,
+            diagnostics: loaded.diagnostics,
+            config,
+        }
+    }
+    /// copy of what loaded so it can be updated without holding the lock
+    fn loaded(&self) -> Loaded {
+        Loaded {
+            posts: self.posts.clone(),
+            sources: self.sources.clone(),
+            diagnostics: self.diagnostics.clone(),
+        }
+    }
+    /// post of the language, along with the one only on the fallback language when
+    /// `list_untranslated` is enabled, the third item is the language it came from.
+    /// `preview` include the post that not public yet
+    fn list_with_fallback(
+        &self,
+        language: &Language,
+        post: &PostType,
+        preview: bool,
+    ) -> Vec<(&String, &PostData, Option<Language>)> {
+        let now = Utc::now();
+        let shown = |v: &PostData| preview || v.metadata.is_public(now);
+        let own = self.posts.get(language).and_then(|x| x.get(post));
+        let mut list = own
+            .into_iter()
+            .flatten()
+            .filter(|(_, v)| shown(v))
+            .map(|(k, v)| (k, &**v, None))
+            .collect::<Vec<_>>();
+        if !self.config.list_untranslated {
+            return list;
+        }
+        for lang in self.config.fallback_chain(language).into_iter().skip(1) {
+            let Some(other) = self.posts.get(&lang).and_then(|x| x.get(post)) else {
+                continue;
+            };
+            for (k, v) in other.iter().filter(|(_, v)| shown(v)) {
+                // the same post translated with other slug linked by the key
+                let key = translation::key(k, v);
+                let listed = list
+                    .iter()
+                    .any(|(slug, v, _)| *slug == k || translation::key(slug, v) == key);
+                if !listed {
+                    list.push((k, &**v, Some(lang.clone())));
+                }
             }
         }
+        let order = load::order(&self.config, post);
+        list.sort_by(|a, b| load::compare(order, (a.0, a.1), (b.0, b.1)));
+        list
     }
-    // sort by date desc (must be format Y-M-D)
-    out.sort_by(|_, a, _, b| b.metadata.date.cmp(&a.metadata.date));
-    Ok(out)
 }
 
 impl Markdown {
-    async fn init() -> MyResult<MarkdownData> {
-        let path = Path::new(".").join("pages").join("markdown");
-        let en = path.join("en");
-        let id = path.join("id");
-        Ok(HashMap::from_iter([
-            (
-                Language::Eng,
-                HashMap::from_iter([
-                    (PostType::Blog, parse_from_path(en.join("blog")).await?),
-                    (
-                        PostType::Project,
-                        parse_from_path(en.join("project")).await?,
-                    ),
-                ]),
-            ),
-            (
-                Language::Idn,
-                HashMap::from_iter([
-                    (PostType::Blog, parse_from_path(id.join("blog")).await?),
-                    (
-                        PostType::Project,
-                        parse_from_path(id.join("project")).await?,
-                    ),
-                ]),
-            ),
-        ]))
-    }
-    /// get instance of struct on default path ./public/markdown
-    pub async fn new() -> MyResult<Self> {
-        Ok(Markdown(Arc::new(RwLock::new(Self::init().await?))))
+    /// get instance of struct from the `markdown_dir` on config
+    pub async fn new(config: Config) -> MyResult<Self> {
+        let loaded = load::load_all(&config, None).await?;
+        Ok(Markdown {
+            data: Arc::new(RwLock::new(MarkdownData::new(loaded, config))),
+            reloading: Arc::new(Mutex::new(())),
+        })
     }
-    /// reload data to match latest
+    /// reload data to match latest, only the changed file parsed again
     pub async fn reload(&self) -> MyResult<()> {
-        *self.write().await = Self::init().await?;
+        let config = self.config().await;
+        self.reload_with(config).await
+    }
+    /// reload data with new config, everything parsed again if the config changed.
+    /// the old one keep serving if it fail and the write lock only held for the swap
+    pub async fn reload_with(&self, config: Config) -> MyResult<()> {
+        let _guard = self.reloading.lock().await;
+        let (old, same) = {
+            let data = self.read().await;
+            (data.loaded(), data.config == config)
+        };
+        let loaded = load::load_all(&config, same.then_some(&old)).await?;
+        let data = MarkdownData::new(loaded, config);
+        *self.write().await = data;
+        Ok(())
+    }
+    /// reload single post from its path `{language}/{post}/{slug}.md`, the post removed
+    /// if the file no longer exist
+    pub async fn reload_file(&self, path: impl AsRef<Path>) -> MyResult<()> {
+        let _guard = self.reloading.lock().await;
+        let (mut loaded, config) = {
+            let data = self.read().await;
+            (data.loaded(), data.config.clone())
+        };
+        if load::load_one(path.as_ref(), &config, &mut loaded).await? {
+            let data = MarkdownData::new(loaded, config);
+            *self.write().await = data;
+        }
         Ok(())
     }
-    /// listing all the post 6 per page
+    /// the broken post skipped on lenient mode
+    pub async fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.read().await.diagnostics.clone()
+    }
+    /// config of the currently loaded data
+    pub async fn config(&self) -> Config {
+        self.read().await.config.clone()
+    }
+    /// every language of the currently loaded data
+    pub async fn languages(&self) -> Vec<Language> {
+        self.read().await.config.languages()
+    }
+    /// the language used when nothing else decided
+    pub async fn default_language(&self) -> Language {
+        self.read().await.config.default_language()
+    }
+    /// collection the intro page featured post come from
+    pub async fn featured_collection(&self) -> PostType {
+        PostType::new(&self.read().await.config.featured_collection)
+    }
+    /// how the code block are coloured
+    pub async fn highlight(&self) -> Highlight {
+        self.read().await.config.highlight
+    }
+    /// css for the server side highlighted code block
+    pub async fn highlight_css(&self) -> MyResult<String> {
+        highlight::theme_css(&self.read().await.config.highlight_theme).ok_or(Error::FileError)
+    }
+    /// every collection of the currently loaded data
+    pub async fn post_types(&self) -> Vec<PostType> {
+        self.read().await.config.post_types()
+    }
+    /// config of the collection, `None` if its not configured
+    pub async fn collection(&self, post: &PostType) -> Option<CollectionConfig> {
+        self.read().await.config.collection(post).cloned()
+    }
+    /// listing the post that match the filter, `page_size` of the collection per page.
+    /// `PageNotFound` for page 0 or past the last page
     async fn data_list(
         &self,
         language: Language,
         post: PostType,
         page: usize,
-        func: impl Fn((&String, &PostData)) -> Option<(String, MetaData)>,
-    ) -> PostList {
-        let range;
+        preview: bool,
+        filter: PostFilter,
+    ) -> MyResult<PostList> {
+        let now = Utc::now();
         let binding_crap = self.read().await;
-        let data = binding_crap.get(&language).unwrap().get(&post).unwrap();
-        if data.len() < page * 6 {
-            range = (page - 1) * 6..data.len();
-        } else {
-            range = (page - 1) * 6..page * 6;
+        let filter = filter.normalized(&binding_crap.config.tag_aliases);
+        let mut data = binding_crap
+            .list_with_fallback(&language, &post, preview)
+            .into_iter()
+            .filter(|(_, v, _)| filter.matches(&v.metadata))
+            .collect::<Vec<_>>();
+        // the pinned one float to the top, only on the plain list
+        if filter.is_empty() {
+            data.sort_by_key(|(_, v, _)| !v.metadata.pinned);
         }
-        let mut endd = data.len() / 6;
-        (data.len() % 6 != 0).then(|| endd.add_assign(1));
-        let end = (1..endd + 1).into_iter().collect();
-        let pagination = Pagination { current: page, end };
-        PostList {
+        let size = binding_crap
+            .config
+            .collection(&post)
+            .map_or(6, |c| c.page_size);
+        let (data, pagination) = pagination::paginate(data, page, size)?;
+        Ok(PostList {
             data: data
-                .get_range(range)
-                .unwrap()
-                .into_iter()
-                .filter_map(func)
+                .iter()
+                .map(|(k, v, fallback)| (k.to_string(), v.metadata.shown(fallback.clone(), now)))
                 .collect(),
             pagination,
-        }
-    }
-    pub async fn list(&self, language: Language, post: PostType, page: usize) -> PostList {
-        self.data_list(language, post, page, |(k, v)| {
-            Some((k.to_owned(), v.metadata.to_owned()))
         })
-        .await
     }
-    /// listing all the post that have specific tag
-    pub async fn list_from_tag(
+    /// `preview` include the draft, scheduled and expired post for the admin. the pinned
+    /// post shown first
+    pub async fn list(
         &self,
         language: Language,
         post: PostType,
-        tag: impl ToString,
         page: usize,
-    ) -> PostList {
-        self.data_list(language, post, page, |(k, v)| {
-            v.metadata
-                .tags
-                .contains(&tag.to_string())
-                .then_some((k.to_owned(), v.metadata.to_owned()))
-        })
-        .await
+        preview: bool,
+    ) -> MyResult<PostList> {
+        self.data_list(language, post, page, preview, PostFilter::default())
+            .await
     }
-    /// get the rendered html and metadata
-    pub async fn get_post(
+    /// listing the post that match the tags and date range
+    pub async fn list_filtered(
         &self,
         language: Language,
         post: PostType,
-        slug: impl ToString,
-    ) -> MyResult<PostData> {
+        filter: PostFilter,
+        page: usize,
+        preview: bool,
+    ) -> MyResult<PostList> {
+        self.data_list(language, post, page, preview, filter).await
+    }
+    /// post count of every year and month for the archive, only the post shown on the list
+    pub async fn archive(
+        &self,
+        language: Language,
+        post: PostType,
+        preview: bool,
+    ) -> Vec<ArchiveYear> {
+        let data = self.read().await;
+        let list = data.list_with_fallback(&language, &post, preview);
+        archive::archive(list.into_iter().map(|(_, v, _)| v.metadata.date))
+    }
+    /// every post written on the year or the month, newest first. `None` when there is
+    /// nothing or the month is not valid
+    pub async fn archive_posts(
+        &self,
+        language: Language,
+        post: PostType,
+        year: i32,
+        month: Option<u32>,
+        preview: bool,
+    ) -> Option<IndexMap<String, MetaData>> {
+        let now = Utc::now();
+        let filter = PostFilter::period(year, month)?;
+        let data = self.read().await;
+        let mut list = data.list_with_fallback(&language, &post, preview);
+        list.retain(|(_, v, _)| filter.matches(&v.metadata));
+        list.sort_by(|a, b| b.1.metadata.date.cmp(&a.1.metadata.date).then(a.0.cmp(b.0)));
+        let posts = list
+            .into_iter()
+            .map(|(k, v, fallback)| (k.to_owned(), v.metadata.shown(fallback, now)))
+            .collect::<IndexMap<_, _>>();
+        (!posts.is_empty()).then_some(posts)
+    }
+    /// every public post without pagination, on the collection order
+    pub async fn all(&self, language: Language, post: PostType) -> IndexMap<String, MetaData> {
+        let now = Utc::now();
         self.read()
             .await
+            .posts
             .get(&language)
             .unwrap()
             .get(&post)
             .unwrap()
-            .get(&slug.to_string())
-            .cloned()
+            .iter()
+            .filter(|(_, v)| v.metadata.is_public(now))
+            .map(|(k, v)| (k.to_owned(), v.metadata.to_owned()))
+            .collect()
+    }
+    /// the public featured post of `featured_collection` for the intro page, on the
+    /// list order
+    pub async fn featured(&self, language: Language) -> IndexMap<String, MetaData> {
+        let now = Utc::now();
+        let data = self.read().await;
+        let post = PostType::new(&data.config.featured_collection);
+        data.list_with_fallback(&language, &post, false)
+            .into_iter()
+            .filter(|(_, v, _)| v.metadata.featured)
+            .map(|(k, v, fallback)| (k.to_owned(), v.metadata.shown(fallback, now)))
+            .collect()
+    }
+    /// listing all the post that have specific tag
+    pub async fn list_from_tag(
+        &self,
+        language: Language,
+        post: PostType,
+        tag: impl ToString,
+        page: usize,
+        preview: bool,
+    ) -> MyResult<PostList> {
+        let filter = PostFilter {
+            tags: vec![tag.to_string()],
+            ..PostFilter::default()
+        };
+        self.data_list(language, post, page, preview, filter).await
+    }
+    /// feed of the newest post, only the one with the tag if specified
+    async fn data_feed(
+        &self,
+        format: FeedFormat,
+        site: Site<'_>,
+        language: Language,
+        post: PostType,
+        tag: Option<&str>,
+    ) -> String {
+        let binding = self.read().await;
+        let data = binding.posts.get(&language).unwrap().get(&post).unwrap();
+        let name = binding
+            .config
+            .collection(&post)
+            .map_or(post.prefix(), |c| c.name());
+        let path = format!("/{language}/{post}");
+        let (title, feed_path, page_path) = match tag {
+            Some(tag) => (
+                format!("{name} #{tag}"),
+                format!("{path}/tag/{tag}"),
+                format!("{path}/tag/{tag}/1"),
+            ),
+            None => (name.to_owned(), path.clone(), path.clone()),
+        };
+        // the same filter as the tag list page
+        let filter = PostFilter {
+            tags: tag.into_iter().map(str::to_owned).collect(),
+            ..PostFilter::default()
+        }
+        .normalized(&binding.config.tag_aliases);
+        let now = Utc::now();
+        let mut posts = data
+            .iter()
+            .map(|(k, v)| (k, &**v))
+            .filter(|(_, v)| v.metadata.is_public(now) && filter.matches(&v.metadata))
+            .collect::<Vec<_>>();
+        // feed always newest first whatever the collection order
+        posts.sort_by_key(|(_, v)| std::cmp::Reverse(v.metadata.date));
+        FeedData {
+            title,
+            language: language.to_string(),
+            path,
+            feed_path,
+            page_path,
+            posts,
+        }
+        .render(format, site)
+    }
+    /// rss or atom feed of the post type
+    pub async fn feed(
+        &self,
+        format: FeedFormat,
+        site: Site<'_>,
+        language: Language,
+        post: PostType,
+    ) -> String {
+        self.data_feed(format, site, language, post, None).await
+    }
+    /// rss or atom feed of the post that have specific tag
+    pub async fn feed_from_tag(
+        &self,
+        format: FeedFormat,
+        site: Site<'_>,
+        language: Language,
+        post: PostType,
+        tag: impl AsRef<str>,
+    ) -> String {
+        let aliases = self.read().await.config.tag_aliases.clone();
+        let tag = tags::normalize(tag.as_ref(), &aliases);
+        self.data_feed(format, site, language, post, Some(&tag))
+            .await
+    }
+    /// get the rendered html and metadata, when its not translated yet the first one found
+    /// on the fallback chain returned with `metadata.fallback_language` set. the post that
+    /// not public yet only found on `preview`
+    pub async fn get_post(
+        &self,
+        language: Language,
+        post: PostType,
+        slug: impl ToString,
+        preview: bool,
+    ) -> MyResult<PostData> {
+        let now = Utc::now();
+        let data = self.read().await;
+        let slug = slug.to_string();
+        data.config
+            .fallback_chain(&language)
+            .into_iter()
+            .find_map(|lang| {
+                let found = data.posts.get(&lang)?.get(&post)?.get(&slug)?;
+                let status = found.metadata.status_at(now);
+                if !preview && status != PostStatus::Published {
+                    return None;
+                }
+                let mut found = PostData::clone(found);
+                found.metadata.status = status;
+                if lang != language {
+                    found.metadata.fallback_language = Some(lang);
+                }
+                Some(found)
+            })
             .ok_or(Error::FileError)
     }
+    /// slug of the translation on `language` when `slug` only exist on the fallback
+    /// language but translated with other slug, so the visitor can be sent there
+    pub async fn translated_slug(
+        &self,
+        language: Language,
+        post: PostType,
+        slug: &str,
+        preview: bool,
+    ) -> Option<String> {
+        let now = Utc::now();
+        let data = self.read().await;
+        let shown = |lang: &Language, slug: &str| {
+            data.posts
+                .get(lang)?
+                .get(&post)?
+                .get(slug)
+                .filter(|x| preview || x.metadata.is_public(now))
+        };
+        if shown(&language, slug).is_some() {
+            return None;
+        }
+        data.config
+            .fallback_chain(&language)
+            .into_iter()
+            .skip(1)
+            .find_map(|lang| {
+                let key = translation::key(slug, shown(&lang, slug)?);
+                let other = data.translations.slug_on(&post, &key, &language)?;
+                shown(&language, other).map(|_| other.to_owned())
+            })
+    }
+    /// the post on every language, linked by the same slug or `translation_key`. the
+    /// translation that not public yet treated as missing
+    pub async fn translations(
+        &self,
+        language: Language,
+        post: PostType,
+        slug: impl AsRef<str>,
+    ) -> Vec<Translation> {
+        let now = Utc::now();
+        let data = self.read().await;
+        let mut translations = data.translations.translations(
+            &data.posts,
+            &data.config,
+            &language,
+            &post,
+            slug.as_ref(),
+        );
+        for translation in translations.iter_mut().filter(|t| t.translated) {
+            let public = data
+                .posts
+                .get(&translation.language)
+                .and_then(|x| x.get(&post))
+                .and_then(|x| x.get(&translation.slug))
+                .is_some_and(|x| x.metadata.is_public(now));
+            if !public {
+                translation.slug = slug.as_ref().to_owned();
+                translation.translated = false;
+            }
+        }
+        translations
+    }
+    /// previous and next post by date along with the related one, among the same post
+    /// shown on the list
+    pub async fn navigation(
+        &self,
+        language: Language,
+        post: PostType,
+        slug: impl AsRef<str>,
+        preview: bool,
+    ) -> Navigation {
+        let data = self.read().await;
+        let list = data.list_with_fallback(&language, &post, preview);
+        data.related
+            .navigation(&list, &language, &post, slug.as_ref(), Utc::now())
+    }
+    /// every tag of the collection with how many post have it, ordered by name
+    pub async fn tags(&self, language: Language, post: PostType, preview: bool) -> Vec<TagCount> {
+        let data = self.read().await;
+        // the same post the list page show
+        let list = data.list_with_fallback(&language, &post, preview);
+        tags::counts(list.into_iter().map(|(_, content, _)| content))
+    }
+    /// every part of the series with the `current` post marked
+    pub async fn series(
+        &self,
+        language: Language,
+        name: impl AsRef<str>,
+        current: Option<(&PostType, &str)>,
+        preview: bool,
+    ) -> Option<Series> {
+        let data = self.read().await;
+        data.series.series(
+            &data.posts,
+            &language,
+            name.as_ref(),
+            current,
+            preview,
+            Utc::now(),
+        )
+    }
+    /// every series of the language ordered by name
+    pub async fn all_series(&self, language: Language, preview: bool) -> Vec<Series> {
+        let data = self.read().await;
+        data.series.all(&data.posts, &language, preview, Utc::now())
+    }
+    /// every post that miss translation or translated before the original last changed
+    pub async fn translation_report(&self) -> Vec<TranslationStatus> {
+        let data = self.read().await;
+        data.translations.report(&data.posts, &data.config)
+    }
+    /// full text search over title, description, tags and content of every post
+    pub async fn search(
+        &self,
+        language: Language,
+        query: impl AsRef<str>,
+        page: usize,
+    ) -> MyResult<SearchResult> {
+        let data = self.read().await;
+        let size = data.config.search_page_size;
+        data.search
+            .search(&data.posts, language, query.as_ref(), page, size)
+    }
 }
@@ -0,0 +1,259 @@
+use crate::{
+    parse_content, Config, Diagnostic, Error, Language, MyResult, PostData, PostType, Posts,
+    SortOrder,
+};
+use indexmap::IndexMap;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    ffi::OsStr,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::ErrorKind,
+    path::Path,
+    sync::Arc,
+    time::SystemTime,
+};
+use tokio::{sync::Semaphore, task::JoinSet};
+
+/// what the loaded post built from, so the unchanged file can be skipped on reload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Source {
+    pub modified: Option<SystemTime>,
+    hash: u64,
+}
+
+pub(crate) type Key = (Language, PostType, String);
+pub(crate) type Sources = HashMap<Key, Source>;
+
+/// everything produced by loading, the diagnostics only filled on lenient mode
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Loaded {
+    pub posts: Posts,
+    pub sources: Sources,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Loaded {
+    /// lenient mode keep going by recording the broken file, strict mode fail right away
+    fn failed(&mut self, config: &Config, err: Error) -> MyResult<()> {
+        match Diagnostic::new(&err) {
+            Some(diagnostic) if config.lenient => {
+                log::warn!("skipping broken post: {err}");
+                self.diagnostics
+                    .retain(|d| !same_post(&d.path, &diagnostic.path, config));
+                self.diagnostics.push(diagnostic);
+                Ok(())
+            }
+            _ => Err(err),
+        }
+    }
+}
+
+/// the language, post type and slug of the markdown file from the last 3 part of its path
+pub(crate) fn locate(path: &Path, config: &Config) -> Option<Key> {
+    if path.extension() != Some(OsStr::new("md")) {
+        return None;
+    }
+    let slug = path.file_stem()?.to_str()?.to_owned();
+    let mut parent = path.parent()?.components().rev();
+    let dir = parent.next()?.as_os_str().to_str()?;
+    let language = parent.next()?.as_os_str().to_str()?;
+    let language = config.languages.iter().find(|l| l.code == language)?;
+    let post = config.collections.iter().find(|c| c.dir() == dir)?;
+    Some((
+        Language::new(&language.code),
+        PostType::new(&post.prefix),
+        slug,
+    ))
+}
+
+/// the watcher give absolute path while the first load use the relative one, so they
+/// compared by where the post live
+fn same_post(a: &Path, b: &Path, config: &Config) -> bool {
+    a == b || locate(a, config).is_some_and(|key| locate(b, config) == Some(key))
+}
+
+/// the date compared as the moment in time whatever its timezone, the one written
+/// without time is midnight UTC. the slug keep the order stable since the file finished
+/// loading on random order
+pub(crate) fn compare(
+    order: SortOrder,
+    (k1, a): (&String, &PostData),
+    (k2, b): (&String, &PostData),
+) -> Ordering {
+    match order {
+        SortOrder::Newest => b.metadata.date.cmp(&a.metadata.date),
+        SortOrder::Oldest => a.metadata.date.cmp(&b.metadata.date),
+        SortOrder::Title => a.metadata.title.cmp(&b.metadata.title),
+    }
+    .then(k1.cmp(k2))
+}
+
+fn sort(list: &mut IndexMap<String, Arc<PostData>>, order: SortOrder) {
+    list.sort_by(|k1, a, k2, b| compare(order, (k1, a), (k2, b)));
+}
+
+pub(crate) fn order(config: &Config, post: &PostType) -> SortOrder {
+    config
+        .collection(post)
+        .map_or_else(SortOrder::default, |c| c.sort)
+}
+
+async fn modified(path: &Path) -> Option<SystemTime> {
+    tokio::fs::metadata(path).await.ok()?.modified().ok()
+}
+
+/// parse the file unless the old one have the same mtime or the same content, the
+/// parsing itself run on blocking thread since its cpu bound
+async fn load_file(
+    path: &Path,
+    config: Arc<Config>,
+    old: Option<(Arc<PostData>, Source)>,
+) -> MyResult<(Arc<PostData>, Source)> {
+    let modified = modified(path).await;
+    if let Some((post, source)) = &old {
+        if modified.is_some() && source.modified == modified {
+            return Ok((post.clone(), *source));
+        }
+    }
+    let text = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|err| Error::ReadError {
+            path: path.to_owned(),
+            reason: err.to_string(),
+        })?;
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    let source = Source {
+        modified,
+        hash: hasher.finish(),
+    };
+    match old {
+        Some((post, old)) if old.hash == source.hash => Ok((post, source)),
+        _ => {
+            let path = path.to_owned();
+            let post = tokio::task::spawn_blocking(move || parse_content(&path, &text, &config))
+                .await
+                .map_err(|_| Error::FileError)??;
+            Ok((Arc::new(post), source))
+        }
+    }
+}
+
+/// load every post concurrently, reusing the unchanged one from `old` if given
+pub(crate) async fn load_all(config: &Config, old: Option<&Loaded>) -> MyResult<Loaded> {
+    let concurrency = match config.load_concurrency {
+        0 => std::thread::available_parallelism().map_or(4, |n| n.get()),
+        n => n,
+    };
+    let limit = Arc::new(Semaphore::new(concurrency));
+    let shared = Arc::new(config.clone());
+    let mut loaded = Loaded::default();
+    let mut tasks = JoinSet::new();
+    for language in config.languages() {
+        for collection in &config.collections {
+            let post = PostType::new(&collection.prefix);
+            loaded
+                .posts
+                .entry(language.clone())
+                .or_default()
+                .insert(post.clone(), IndexMap::new());
+            let dir = config
+                .markdown_dir
+                .join(language.code())
+                .join(collection.dir());
+            // newly added language may not have any post yet
+            let mut list = match tokio::fs::read_dir(&dir).await {
+                Ok(list) => list,
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => {
+                    return Err(Error::ReadError {
+                        path: dir,
+                        reason: err.to_string(),
+                    })
+                }
+            };
+            while let Ok(Some(entry)) = list.next_entry().await {
+                let path = entry.path();
+                let Some(key) = locate(&path, config) else {
+                    continue;
+                };
+                let previous = old.and_then(|old| {
+                    let data = old.posts.get(&language)?.get(&post)?.get(&key.2)?;
+                    Some((data.clone(), *old.sources.get(&key)?))
+                });
+                let (limit, config) = (limit.clone(), shared.clone());
+                tasks.spawn(async move {
+                    let _permit = limit.acquire_owned().await;
+                    (key, load_file(&path, config, previous).await)
+                });
+            }
+        }
+    }
+    // returning early drop the JoinSet which abort the rest of the task
+    while let Some(task) = tasks.join_next().await {
+        let (key, result) = task.map_err(|_| Error::FileError)?;
+        let (data, source) = match result {
+            Ok(x) => x,
+            Err(err) => {
+                loaded.failed(config, err)?;
+                continue;
+            }
+        };
+        let list = loaded.posts.get_mut(&key.0).and_then(|x| x.get_mut(&key.1));
+        if let Some(list) = list {
+            list.insert(key.2.to_owned(), data);
+        }
+        loaded.sources.insert(key, source);
+    }
+    for (post, list) in loaded.posts.values_mut().flat_map(|x| x.iter_mut()) {
+        sort(list, order(config, post));
+    }
+    // the file finished on random order
+    loaded.diagnostics.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(loaded)
+}
+
+/// load or remove (when its deleted) single post, return whether anything changed
+pub(crate) async fn load_one(path: &Path, config: &Config, loaded: &mut Loaded) -> MyResult<bool> {
+    let key = locate(path, config).ok_or(Error::FileError)?;
+    let had_diagnostic = loaded
+        .diagnostics
+        .iter()
+        .any(|d| same_post(&d.path, path, config));
+    loaded
+        .diagnostics
+        .retain(|d| !same_post(&d.path, path, config));
+    let list = loaded
+        .posts
+        .get_mut(&key.0)
+        .and_then(|x| x.get_mut(&key.1))
+        .ok_or(Error::FileError)?;
+    if tokio::fs::metadata(path).await.is_err() {
+        loaded.sources.remove(&key);
+        return Ok(list.shift_remove(&key.2).is_some() || had_diagnostic);
+    }
+    let previous = list
+        .get(&key.2)
+        .cloned()
+        .zip(loaded.sources.get(&key).copied());
+    let old_hash = previous.as_ref().map(|(_, old)| old.hash);
+    let (data, source) = match load_file(path, Arc::new(config.clone()), previous).await {
+        Ok(x) => x,
+        Err(err) => {
+            // the broken one taken down so it dont keep showing the old content
+            list.shift_remove(&key.2);
+            loaded.sources.remove(&key);
+            loaded.failed(config, err)?;
+            return Ok(true);
+        }
+    };
+    if old_hash == Some(source.hash) {
+        loaded.sources.insert(key, source);
+        return Ok(had_diagnostic);
+    }
+    list.insert(key.2.to_owned(), data);
+    sort(list, order(config, &key.1));
+    loaded.sources.insert(key, source);
+    Ok(true)
+}
@@ -0,0 +1,96 @@
+use crate::{Error, MyResult};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// pagination struct
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Pagination {
+    pub current: usize,
+    /// every page number, for the page links
+    pub end: Vec<usize>,
+    /// how many item per page
+    pub size: usize,
+    /// how many item in total, not only the one on this page
+    pub total: usize,
+    pub pages: usize,
+    pub previous: Option<usize>,
+    pub next: Option<usize>,
+}
+
+impl Pagination {
+    /// the first page always exist even when there is nothing on it, page 0 and the one
+    /// past the last page are not found
+    pub fn new(page: usize, size: usize, total: usize) -> MyResult<Self> {
+        let size = size.max(1);
+        let pages = total.div_ceil(size);
+        if page == 0 || page > pages.max(1) {
+            return Err(Error::PageNotFound { page, pages });
+        }
+        Ok(Self {
+            current: page,
+            end: (1..=pages).collect(),
+            size,
+            total,
+            pages,
+            previous: (page > 1).then(|| page - 1),
+            next: (page < pages).then(|| page + 1),
+        })
+    }
+    /// index of the item shown on the page
+    pub fn range(&self) -> Range<usize> {
+        let start = (self.current - 1) * self.size;
+        start.min(self.total)..(start + self.size).min(self.total)
+    }
+}
+
+/// only the item on the page, the list must be filtered already
+pub(crate) fn paginate<T>(
+    items: impl IntoIterator<Item = T>,
+    page: usize,
+    size: usize,
+) -> MyResult<(Vec<T>, Pagination)> {
+    let items = items.into_iter().collect::<Vec<_>>();
+    let pagination = Pagination::new(page, size, items.len())?;
+    let range = pagination.range();
+    let items = items
+        .into_iter()
+        .skip(range.start)
+        .take(range.len())
+        .collect();
+    Ok((items, pagination))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_zero_not_found() {
+        let err = Pagination::new(0, 6, 10).unwrap_err();
+        assert!(matches!(err, Error::PageNotFound { page: 0, pages: 2 }));
+    }
+
+    #[test]
+    fn page_past_the_end_not_found() {
+        assert!(Pagination::new(2, 6, 7).is_ok());
+        let err = Pagination::new(3, 6, 7).unwrap_err();
+        assert!(matches!(err, Error::PageNotFound { page: 3, pages: 2 }));
+    }
+
+    #[test]
+    fn empty_list_have_first_page() {
+        let pagination = Pagination::new(1, 6, 0).unwrap();
+        assert_eq!(pagination.pages, 0);
+        assert_eq!(pagination.range(), 0..0);
+        assert_eq!((pagination.previous, pagination.next), (None, None));
+        assert!(Pagination::new(2, 6, 0).is_err());
+    }
+
+    #[test]
+    fn zero_size_treated_as_one() {
+        let pagination = Pagination::new(3, 0, 3).unwrap();
+        assert_eq!(pagination.size, 1);
+        assert_eq!(pagination.range(), 2..3);
+        assert_eq!(pagination.next, None);
+    }
+}
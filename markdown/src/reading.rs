@@ -0,0 +1,125 @@
+use pulldown_cmark::{Event, Tag, TagEnd};
+
+/// how long the auto excerpt can be, cut on the word before it
+const EXCERPT_CHARS: usize = 200;
+
+/// chinese, japanese and korean script that written without space between words, each
+/// character counted as single word
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}' // hiragana and katakana
+        | '\u{3400}'..='\u{4DBF}' // han extension a
+        | '\u{4E00}'..='\u{9FFF}' // han
+        | '\u{AC00}'..='\u{D7AF}' // hangul
+        | '\u{F900}'..='\u{FAFF}' // han compatibility
+    )
+}
+
+/// word on space separated script plus every cjk character
+pub(crate) fn word_count(text: &str) -> usize {
+    text.split_whitespace()
+        .map(|word| {
+            let cjk = word.chars().filter(|c| is_cjk(*c)).count();
+            let rest = word
+                .split(is_cjk)
+                .filter(|x| x.chars().any(char::is_alphanumeric))
+                .count();
+            cjk + rest
+        })
+        .sum()
+}
+
+/// minutes to read, at least a minute
+pub(crate) fn reading_time(words: usize, words_per_minute: usize) -> usize {
+    words.div_ceil(words_per_minute.max(1)).max(1)
+}
+
+/// text of the paragraph only, the heading and code block dont read well on the excerpt
+pub(crate) fn paragraph_text(events: &[Event]) -> String {
+    let mut text = String::new();
+    let mut inside = false;
+    for event in events {
+        match event {
+            Event::Start(Tag::Paragraph) => inside = true,
+            Event::End(TagEnd::Paragraph) => {
+                inside = false;
+                text.push(' ');
+            }
+            Event::Text(t) | Event::Code(t) if inside => text.push_str(t),
+            Event::SoftBreak | Event::HardBreak if inside => text.push(' '),
+            _ => (),
+        }
+    }
+    text
+}
+
+/// start of the plain text content, cut on the word boundary
+pub(crate) fn excerpt(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= EXCERPT_CHARS {
+        return text;
+    }
+    let end = text
+        .char_indices()
+        .nth(EXCERPT_CHARS)
+        .map_or(text.len(), |(i, _)| i);
+    let cut = &text[..end];
+    // cjk text may not have any space to cut on
+    let cut = match cut.rfind(' ') {
+        Some(space) if !cut.ends_with(is_cjk) => &cut[..space],
+        _ => cut,
+    };
+    format!(
+        "{}…",
+        cut.trim_end_matches(|c: char| c.is_ascii_punctuation())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_content, Config};
+    use std::path::Path;
+
+    #[test]
+    fn excerpt_skip_heading_and_code() {
+        let text = "---
+title: Hello
+date: 2024-01-05
+image: /default.png
+tags: []
+---
+# Heading Same
+
+```rust
+fn main() { println!(\"<hi>\"); }
+```
+
+First *real* paragraph with `code`.
+Second line.
+
+## Other
+
+Last one.
+";
+        let post = parse_content(Path::new("en/blog/hello.md"), text, &Config::default()).unwrap();
+        assert_eq!(
+            post.metadata.excerpt,
+            "First real paragraph with code. Second line. Last one."
+        );
+    }
+
+    #[test]
+    fn description_kept_as_excerpt() {
+        let text = "---
+title: Hello
+date: 2024-01-05
+description: written by hand
+image: /default.png
+tags: []
+---
+Body.
+";
+        let post = parse_content(Path::new("en/blog/hello.md"), text, &Config::default()).unwrap();
+        assert_eq!(post.metadata.excerpt, "written by hand");
+    }
+}
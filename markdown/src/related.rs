@@ -0,0 +1,140 @@
+use crate::{load::Key, search::tokenize, Language, MetaData, PostData, PostType, Posts};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// how many related post shown
+const RELATED: usize = 3;
+/// a shared tag always worth more than the text similarity, which is at most 1
+const TAG_WEIGHT: f32 = 1.0;
+/// only the most important words of each post compared
+const TERMS: usize = 50;
+
+/// post linked from other post page
+#[derive(Serialize, Clone, Debug)]
+pub struct PostLink {
+    pub slug: String,
+    pub metadata: MetaData,
+}
+
+/// where to go after reading the post
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct Navigation {
+    /// the older one by date
+    pub previous: Option<PostLink>,
+    /// the newer one by date
+    pub next: Option<PostLink>,
+    /// ranked by shared tags then text similarity
+    pub related: Vec<PostLink>,
+}
+
+/// tf-idf weight of the most important words, normalized so the dot product of two post
+/// is their cosine similarity
+type Terms = HashMap<String, f32>;
+
+/// words of every post, weighted against the other post on the same collection
+#[derive(Debug, Default)]
+pub(crate) struct RelatedIndex(HashMap<Key, Terms>);
+
+impl RelatedIndex {
+    pub(crate) fn new(data: &Posts) -> Self {
+        let mut index = HashMap::new();
+        for (language, posts) in data {
+            for (post, list) in posts {
+                let counts = list
+                    .iter()
+                    .map(|(slug, content)| {
+                        let mut count = HashMap::<String, f32>::new();
+                        // short words are mostly the common one like "the" or "dan"
+                        for word in tokenize(&content.text).filter(|w| w.chars().count() > 2) {
+                            *count.entry(word).or_default() += 1.0;
+                        }
+                        (slug, count)
+                    })
+                    .collect::<Vec<_>>();
+                let mut found_on = HashMap::<&str, f32>::new();
+                for (_, count) in &counts {
+                    for word in count.keys() {
+                        *found_on.entry(word).or_default() += 1.0;
+                    }
+                }
+                let total = counts.len() as f32;
+                for (slug, count) in &counts {
+                    let mut terms = count
+                        .iter()
+                        .map(|(word, tf)| (word, tf * (total / found_on[word.as_str()]).ln()))
+                        .filter(|(_, weight)| *weight > 0.0)
+                        .collect::<Vec<_>>();
+                    terms.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+                    terms.truncate(TERMS);
+                    let norm = terms.iter().map(|(_, w)| w * w).sum::<f32>().sqrt();
+                    let terms = terms
+                        .into_iter()
+                        .map(|(word, weight)| (word.to_owned(), weight / norm))
+                        .collect();
+                    index.insert((language.clone(), post.clone(), slug.to_string()), terms);
+                }
+            }
+        }
+        Self(index)
+    }
+
+    fn similarity(&self, a: &Key, b: &Key) -> f32 {
+        let (Some(a), Some(b)) = (self.0.get(a), self.0.get(b)) else {
+            return 0.0;
+        };
+        a.iter()
+            .filter_map(|(word, weight)| b.get(word).map(|other| weight * other))
+            .sum()
+    }
+
+    /// previous, next and related post of `slug` among the `list` of the collection, the
+    /// third item of the list is the language it came from when its a fallback
+    pub(crate) fn navigation(
+        &self,
+        list: &[(&String, &PostData, Option<Language>)],
+        language: &Language,
+        post: &PostType,
+        slug: &str,
+        now: DateTime<Utc>,
+    ) -> Navigation {
+        let key = |(slug, _, fallback): &(&String, &PostData, Option<Language>)| {
+            let language = fallback.as_ref().unwrap_or(language).clone();
+            (language, post.clone(), slug.to_string())
+        };
+        let link = |(slug, content, fallback): &(&String, &PostData, Option<Language>)| PostLink {
+            slug: slug.to_string(),
+            metadata: content.metadata.shown(fallback.clone(), now),
+        };
+        let mut by_date = list.iter().collect::<Vec<_>>();
+        by_date.sort_by(|a, b| a.1.metadata.date.cmp(&b.1.metadata.date).then(a.0.cmp(b.0)));
+        let Some(at) = by_date.iter().position(|x| x.0 == slug) else {
+            return Navigation::default();
+        };
+        let current = by_date[at];
+        let mut related = list
+            .iter()
+            .filter(|x| x.0 != slug)
+            .filter_map(|other| {
+                let tags = &current.1.metadata.tags;
+                let shared = other.1.metadata.tags.iter().filter(|t| tags.contains(t));
+                let score = shared.count() as f32 * TAG_WEIGHT
+                    + self.similarity(&key(current), &key(other));
+                (score > 0.0).then_some((other, score))
+            })
+            .collect::<Vec<_>>();
+        related.sort_by(|a, b| {
+            b.1.total_cmp(&a.1)
+                .then(b.0 .1.metadata.date.cmp(&a.0 .1.metadata.date))
+        });
+        Navigation {
+            previous: at.checked_sub(1).map(|i| link(by_date[i])),
+            next: by_date.get(at + 1).map(|x| link(x)),
+            related: related
+                .into_iter()
+                .take(RELATED)
+                .map(|(x, _)| link(x))
+                .collect(),
+        }
+    }
+}
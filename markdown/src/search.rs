@@ -0,0 +1,222 @@
+use crate::{pagination, Language, MetaData, MyResult, Pagination, PostType, Posts};
+use chrono::Utc;
+use pulldown_cmark::Event;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// how much each field count toward the score
+const TITLE_WEIGHT: f32 = 5.0;
+const TAG_WEIGHT: f32 = 4.0;
+const DESCRIPTION_WEIGHT: f32 = 2.0;
+const BODY_WEIGHT: f32 = 1.0;
+/// how many words shown around the match on the snippet
+const SNIPPET_BEFORE: usize = 10;
+const SNIPPET_AFTER: usize = 25;
+
+/// single post that match the query
+#[derive(Serialize, Clone, Debug)]
+pub struct SearchHit {
+    pub post: PostType,
+    pub slug: String,
+    pub metadata: MetaData,
+    /// escaped html of the text around the match with the matched words on `<mark>`
+    pub snippet: String,
+    pub score: f32,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SearchResult {
+    pub hits: Vec<SearchHit>,
+    pub total: usize,
+    pub pagination: Pagination,
+}
+
+/// plain text of the markdown body, used for indexing and snippet
+pub fn plain_text(events: &[Event]) -> String {
+    let mut text = String::new();
+    for event in events {
+        match event {
+            Event::Text(t) | Event::Code(t) => text.push_str(t),
+            Event::End(_) | Event::SoftBreak | Event::HardBreak => text.push(' '),
+            _ => (),
+        }
+    }
+    text
+}
+
+/// lowercase words split on anything that isnt alphanumeric
+pub(crate) fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// inverted index of single language, the posting point to position in `docs`
+#[derive(Debug, Default)]
+struct LangIndex {
+    docs: Vec<(PostType, String)>,
+    terms: BTreeMap<String, HashMap<usize, f32>>,
+}
+
+impl LangIndex {
+    fn add(&mut self, doc: usize, text: &str, weight: f32) {
+        for term in tokenize(text) {
+            *self.terms.entry(term).or_default().entry(doc).or_default() += weight;
+        }
+    }
+    /// score of each doc for one query term, the term also match as word prefix
+    /// so "rus" find "rust"
+    fn lookup(&self, term: &str) -> HashMap<usize, f32> {
+        let mut out = HashMap::<usize, f32>::new();
+        let total = self.docs.len() as f32;
+        for (word, postings) in self.terms.range(term.to_owned()..) {
+            if !word.starts_with(term) {
+                break;
+            }
+            // exact word worth more than prefix one, and rare word worth more than common one
+            let exact = if word == term { 1.0 } else { 0.5 };
+            let idf = (1.0 + total / postings.len() as f32).ln();
+            for (doc, weight) in postings {
+                *out.entry(*doc).or_default() += weight * idf * exact;
+            }
+        }
+        out
+    }
+}
+
+/// full text search index of every post per language, rebuilt along with the markdown
+#[derive(Debug, Default)]
+pub struct SearchIndex(HashMap<Language, LangIndex>);
+
+impl SearchIndex {
+    pub fn new(data: &Posts) -> Self {
+        let mut index = HashMap::new();
+        for (language, posts) in data {
+            let mut lang = LangIndex::default();
+            for (post, list) in posts {
+                for (slug, content) in list {
+                    let doc = lang.docs.len();
+                    lang.docs.push((post.clone(), slug.to_owned()));
+                    let meta = &content.metadata;
+                    lang.add(doc, &meta.title, TITLE_WEIGHT);
+                    lang.add(doc, &meta.tags.join(" "), TAG_WEIGHT);
+                    lang.add(doc, &meta.description, DESCRIPTION_WEIGHT);
+                    lang.add(doc, &content.text, BODY_WEIGHT);
+                }
+            }
+            index.insert(language.clone(), lang);
+        }
+        Self(index)
+    }
+
+    /// ranked search, every word on the query must be found on the post
+    pub fn search(
+        &self,
+        data: &Posts,
+        language: Language,
+        query: &str,
+        page: usize,
+        size: usize,
+    ) -> MyResult<SearchResult> {
+        let terms = tokenize(query).collect::<Vec<_>>();
+        let mut scores: Option<HashMap<usize, f32>> = None;
+        if let Some(index) = self.0.get(&language) {
+            for term in &terms {
+                let found = index.lookup(term);
+                scores = Some(match scores {
+                    None => found,
+                    Some(prev) => prev
+                        .into_iter()
+                        .filter_map(|(doc, score)| found.get(&doc).map(|s| (doc, score + s)))
+                        .collect(),
+                });
+            }
+        }
+        let mut ranked = scores.unwrap_or_default().into_iter().collect::<Vec<_>>();
+        // the index have every post, whether its public decided on each search
+        let now = Utc::now();
+        ranked.retain(|(doc, _)| {
+            let public = || {
+                let (post, slug) = &self.0.get(&language)?.docs[*doc];
+                let content = data.get(&language)?.get(post)?.get(slug)?;
+                Some(content.metadata.is_public(now))
+            };
+            public().unwrap_or(false)
+        });
+        // the same score ordered by post and slug, the doc number follow the map order
+        // that change on every start
+        let docs = self.0.get(&language).map_or(&[][..], |x| &x.docs);
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| docs[a.0].cmp(&docs[b.0])));
+
+        let total = ranked.len();
+        let (ranked, pagination) = pagination::paginate(ranked, page, size)?;
+        let hits = ranked
+            .into_iter()
+            .filter_map(|(doc, score)| {
+                let (post, slug) = &self.0.get(&language)?.docs[doc];
+                let content = data.get(&language)?.get(post)?.get(slug)?;
+                Some(SearchHit {
+                    post: post.clone(),
+                    slug: slug.to_owned(),
+                    metadata: content.metadata.to_owned(),
+                    snippet: snippet(&content.text, &content.metadata.excerpt, &terms),
+                    score,
+                })
+            })
+            .collect();
+        Ok(SearchResult {
+            hits,
+            total,
+            pagination,
+        })
+    }
+}
+
+/// words around the first match on the body, fallback to the excerpt when the body
+/// dont have any match (the post only matched by title or tags)
+fn snippet(text: &str, excerpt: &str, terms: &[String]) -> String {
+    let matched = |word: &str| {
+        let mut words = tokenize(word);
+        words.any(|w| terms.iter().any(|t| w.starts_with(t.as_str())))
+    };
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    let Some(first) = words.iter().position(|w| matched(w)) else {
+        return escape(excerpt);
+    };
+    let start = first.saturating_sub(SNIPPET_BEFORE);
+    let end = (first + SNIPPET_AFTER).min(words.len());
+    let mut out = String::new();
+    if start > 0 {
+        out.push_str("… ");
+    }
+    let body = words[start..end]
+        .iter()
+        .map(|w| {
+            if matched(w) {
+                format!("<mark>{}</mark>", escape(w))
+            } else {
+                escape(w)
+            }
+        })
+        .collect::<Vec<_>>();
+    out.push_str(&body.join(" "));
+    if end < words.len() {
+        out.push_str(" …");
+    }
+    out
+}
@@ -0,0 +1,121 @@
+use crate::{Language, MetaData, PostStatus, PostType, Posts};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// the series a post belong to as written on the front matter
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SeriesMeta {
+    pub name: String,
+    /// order on the series, the post date decide when its not set
+    #[serde(default)]
+    pub part: Option<u32>,
+}
+
+/// single post of the series
+#[derive(Serialize, Clone, Debug)]
+pub struct SeriesPart {
+    pub post: PostType,
+    pub slug: String,
+    pub metadata: MetaData,
+    /// the post currently viewed
+    pub current: bool,
+}
+
+/// every part of the series in order
+#[derive(Serialize, Clone, Debug)]
+pub struct Series {
+    pub name: String,
+    pub parts: Vec<SeriesPart>,
+}
+
+/// post of every series name on each language, already in order
+#[derive(Debug, Default)]
+pub(crate) struct SeriesIndex(HashMap<Language, BTreeMap<String, Vec<(PostType, String)>>>);
+
+impl SeriesIndex {
+    pub(crate) fn new(data: &Posts) -> Self {
+        let mut index = HashMap::<_, BTreeMap<_, Vec<_>>>::new();
+        for (language, posts) in data {
+            let mut found = BTreeMap::<_, Vec<_>>::new();
+            for (post, list) in posts {
+                for (slug, content) in list {
+                    if let Some(series) = &content.metadata.series {
+                        found.entry(series.name.to_owned()).or_default().push((
+                            series.part,
+                            content.metadata.date,
+                            post.clone(),
+                            slug.to_owned(),
+                        ));
+                    }
+                }
+            }
+            for (name, mut parts) in found {
+                // the one without part number after the numbered one
+                parts.sort_by(|a, b| {
+                    (a.0.is_none(), a.0, a.1, &a.3).cmp(&(b.0.is_none(), b.0, b.1, &b.3))
+                });
+                let parts = parts.into_iter().map(|x| (x.2, x.3)).collect();
+                index
+                    .entry(language.clone())
+                    .or_default()
+                    .insert(name, parts);
+            }
+        }
+        Self(index)
+    }
+
+    /// parts of the series that the visitor can see, `preview` include the one not
+    /// public yet. `None` if nothing left
+    pub(crate) fn series(
+        &self,
+        data: &Posts,
+        language: &Language,
+        name: &str,
+        current: Option<(&PostType, &str)>,
+        preview: bool,
+        now: DateTime<Utc>,
+    ) -> Option<Series> {
+        let parts = self
+            .0
+            .get(language)?
+            .get(name)?
+            .iter()
+            .filter_map(|(post, slug)| {
+                let content = data.get(language)?.get(post)?.get(slug)?;
+                let status = content.metadata.status_at(now);
+                if !preview && status != PostStatus::Published {
+                    return None;
+                }
+                let mut metadata = content.metadata.clone();
+                metadata.status = status;
+                Some(SeriesPart {
+                    current: current.is_some_and(|(p, s)| p == post && s == slug),
+                    post: post.clone(),
+                    slug: slug.to_owned(),
+                    metadata,
+                })
+            })
+            .collect::<Vec<_>>();
+        (!parts.is_empty()).then(|| Series {
+            name: name.to_owned(),
+            parts,
+        })
+    }
+
+    /// every series on the language by name
+    pub(crate) fn all(
+        &self,
+        data: &Posts,
+        language: &Language,
+        preview: bool,
+        now: DateTime<Utc>,
+    ) -> Vec<Series> {
+        self.0
+            .get(language)
+            .into_iter()
+            .flat_map(|x| x.keys())
+            .filter_map(|name| self.series(data, language, name, None, preview, now))
+            .collect()
+    }
+}
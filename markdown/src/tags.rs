@@ -0,0 +1,98 @@
+use crate::PostData;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// how many size step on the tag cloud
+const WEIGHTS: usize = 5;
+
+/// single tag on the tag cloud
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TagCount {
+    pub name: String,
+    pub count: usize,
+    /// 1 for the least used up to 5 for the most used, for the font size
+    pub weight: usize,
+}
+
+/// lowercase with the whitespace turned to single `-`, then the alias applied so
+/// "Rust Lang" and "rustlang" can both become "rust"
+pub(crate) fn normalize(tag: &str, aliases: &HashMap<String, String>) -> String {
+    let tag = tag
+        .split_whitespace()
+        .map(|x| x.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("-");
+    let alias = aliases
+        .iter()
+        .find(|(from, _)| normalize(from, &HashMap::new()) == tag);
+    match alias {
+        Some((_, to)) => normalize(to, &HashMap::new()),
+        None => tag,
+    }
+}
+
+/// how many of the `posts` have each tag, by name
+pub(crate) fn counts<'a>(posts: impl IntoIterator<Item = &'a PostData>) -> Vec<TagCount> {
+    let mut found = BTreeMap::<&str, usize>::new();
+    for content in posts {
+        for tag in &content.metadata.tags {
+            *found.entry(tag).or_default() += 1;
+        }
+    }
+    let most = found.values().copied().max().unwrap_or(1);
+    found
+        .into_iter()
+        .map(|(name, count)| TagCount {
+            name: name.to_owned(),
+            count,
+            weight: (count * WEIGHTS).div_ceil(most).clamp(1, WEIGHTS),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Config, Language, Markdown, PostType};
+    use std::path::Path;
+
+    fn write(dir: &Path, file: &str, front: &str) {
+        let path = dir.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let text = format!("---\n{front}date: 2024-01-05\nimage: /default.png\n---\nbody\n");
+        std::fs::write(path, text).unwrap();
+    }
+
+    #[tokio::test]
+    async fn counted_like_the_list() {
+        let dir = std::env::temp_dir().join(format!("tags-{}", std::process::id()));
+        write(
+            &dir,
+            "en/blog/hello.md",
+            "title: Hello\ntags: [Rust Lang, web]\n",
+        );
+        write(&dir, "en/blog/only.md", "title: Only\ntags: [rust]\n");
+        write(
+            &dir,
+            "id/blog/halo.md",
+            "title: Halo\ntranslation_key: hello\ntags: [rust]\n",
+        );
+        let config = Config {
+            markdown_dir: dir.clone(),
+            list_untranslated: true,
+            ..Config::default()
+        };
+        let markdown = Markdown::new(config).await.unwrap();
+        let (id, blog) = (Language::new("id"), PostType::new("blog"));
+        let tags = markdown.tags(id.clone(), blog.clone(), false).await;
+        std::fs::remove_dir_all(&dir).ok();
+
+        // halo stand for hello, only is the untranslated fallback
+        let list = markdown.list(id, blog, 1, false).await.unwrap();
+        assert_eq!(list.data.keys().collect::<Vec<_>>(), ["halo", "only"]);
+        let tags = tags
+            .iter()
+            .map(|x| (x.name.as_str(), x.count))
+            .collect::<Vec<_>>();
+        assert_eq!(tags, [("rust", 2)]);
+    }
+}
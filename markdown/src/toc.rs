@@ -0,0 +1,99 @@
+use pulldown_cmark::{Event, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// single entry of the post table of content, nested by heading level
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Heading {
+    pub level: u8,
+    pub text: String,
+    pub id: String,
+    pub children: Vec<Heading>,
+}
+
+/// turn heading text into url friendly anchor, ex. "Level Two: Intro" is "level-two-intro"
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if (c.is_whitespace() || c == '-' || c == '_') && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_matches('-');
+    if slug.is_empty() {
+        "section".to_owned()
+    } else {
+        slug.to_owned()
+    }
+}
+
+fn unique(slug: String, used: &mut HashSet<String>) -> String {
+    let mut id = slug.clone();
+    let mut count = 1;
+    while used.contains(&id) {
+        id = format!("{slug}-{count}");
+        count += 1;
+    }
+    used.insert(id.clone());
+    id
+}
+
+/// put the heading into the deepest last entry that have lower level than it
+fn insert(list: &mut Vec<Heading>, heading: Heading) {
+    match list.last_mut() {
+        Some(last) if last.level < heading.level => insert(&mut last.children, heading),
+        _ => list.push(heading),
+    }
+}
+
+/// give every heading without explicit `{#id}` a unique slug id (mutating the events so
+/// the rendered html have the same id) and collect them into nested table of content
+pub fn anchor_headings(events: &mut [Event]) -> Vec<Heading> {
+    // explicit id take the priority, so generated one never collide with them
+    let mut used = events
+        .iter()
+        .filter_map(|e| match e {
+            Event::Start(Tag::Heading { id: Some(id), .. }) => Some(id.to_string()),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+    let mut toc = Vec::new();
+    let mut i = 0;
+    while i < events.len() {
+        let Event::Start(Tag::Heading { level, .. }) = &events[i] else {
+            i += 1;
+            continue;
+        };
+        let level = *level as u8;
+        let mut text = String::new();
+        let mut end = i + 1;
+        while end < events.len() {
+            match &events[end] {
+                Event::End(TagEnd::Heading(_)) => break,
+                Event::Text(t) | Event::Code(t) => text.push_str(t),
+                _ => (),
+            }
+            end += 1;
+        }
+        let text = text.trim().to_owned();
+        if let Event::Start(Tag::Heading { id, .. }) = &mut events[i] {
+            if id.is_none() {
+                *id = Some(unique(slugify(&text), &mut used).into());
+            }
+            let id = id.as_ref().map(|x| x.to_string()).unwrap_or_default();
+            insert(
+                &mut toc,
+                Heading {
+                    level,
+                    text,
+                    id,
+                    children: Vec::new(),
+                },
+            );
+        }
+        i = end + 1;
+    }
+    toc
+}
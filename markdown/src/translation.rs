@@ -0,0 +1,148 @@
+use crate::{Config, Language, PostData, PostType, Posts};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// the same post on single language
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Translation {
+    pub language: Language,
+    pub slug: String,
+    /// `false` when its not translated yet, the slug then point to the fallback one
+    pub translated: bool,
+}
+
+/// post that need attention on the translation report
+#[derive(Serialize, Clone, Debug)]
+pub struct TranslationStatus {
+    pub post: PostType,
+    /// the `translation_key` or the slug that link them together
+    pub key: String,
+    /// the default language one, or the first language that have it
+    pub original: Translation,
+    pub missing: Vec<Language>,
+    /// `updated` (or `date` when its not set) older than the original one, so it may not
+    /// follow the latest change
+    pub outdated: Vec<Translation>,
+}
+
+/// what link the translation together, the slug when `translation_key` is not set
+pub(crate) fn key(slug: &str, post: &PostData) -> String {
+    post.metadata
+        .translation_key
+        .clone()
+        .unwrap_or_else(|| slug.to_owned())
+}
+
+/// slug of each language grouped by the translation key
+#[derive(Debug, Default)]
+pub(crate) struct TranslationIndex(HashMap<(PostType, String), HashMap<Language, String>>);
+
+impl TranslationIndex {
+    pub(crate) fn new(data: &Posts) -> Self {
+        let mut index = HashMap::<_, HashMap<_, _>>::new();
+        for (language, posts) in data {
+            for (post, list) in posts {
+                for (slug, content) in list {
+                    index
+                        .entry((post.clone(), key(slug, content)))
+                        .or_default()
+                        .insert(language.clone(), slug.to_owned());
+                }
+            }
+        }
+        Self(index)
+    }
+
+    /// slug of the post with the key on the language
+    pub(crate) fn slug_on(&self, post: &PostType, key: &str, language: &Language) -> Option<&str> {
+        self.0
+            .get(&(post.clone(), key.to_owned()))?
+            .get(language)
+            .map(String::as_str)
+    }
+
+    /// the post on every configured language, the missing one link to the same slug
+    /// which shown from the fallback language. the key taken from the language that
+    /// actually serve the slug, so the fallback one still linked
+    pub(crate) fn translations(
+        &self,
+        data: &Posts,
+        config: &Config,
+        language: &Language,
+        post: &PostType,
+        slug: &str,
+    ) -> Vec<Translation> {
+        let linked = config
+            .fallback_chain(language)
+            .iter()
+            .find_map(|lang| data.get(lang)?.get(post)?.get(slug))
+            .and_then(|content| self.0.get(&(post.clone(), key(slug, content))));
+        config
+            .languages()
+            .into_iter()
+            .map(|lang| match linked.and_then(|x| x.get(&lang)) {
+                Some(found) => Translation {
+                    language: lang,
+                    slug: found.to_owned(),
+                    translated: true,
+                },
+                None => Translation {
+                    language: lang,
+                    slug: slug.to_owned(),
+                    translated: false,
+                },
+            })
+            .collect()
+    }
+
+    /// every post with missing translation or the one updated before the original, the
+    /// front matter date used since the file mtime change on every clone or copy
+    pub(crate) fn report(&self, data: &Posts, config: &Config) -> Vec<TranslationStatus> {
+        let mut languages = config.languages();
+        let default = config.default_language();
+        languages.sort_by_key(|l| *l != default);
+        let mut report = Vec::new();
+        for ((post, key), slugs) in &self.0 {
+            let Some((original, slug)) = languages
+                .iter()
+                .find_map(|l| slugs.get(l).map(|slug| (l.clone(), slug.to_owned())))
+            else {
+                continue;
+            };
+            let updated = |language: &Language, slug: &str| {
+                let meta = &data.get(language)?.get(post)?.get(slug)?.metadata;
+                Some(meta.updated.unwrap_or(meta.date))
+            };
+            let since = updated(&original, &slug);
+            let mut missing = Vec::new();
+            let mut outdated = Vec::new();
+            for language in languages.iter().filter(|l| **l != original) {
+                match slugs.get(language) {
+                    None => missing.push(language.clone()),
+                    Some(found) if updated(language, found) < since => outdated.push(Translation {
+                        language: language.clone(),
+                        slug: found.to_owned(),
+                        translated: true,
+                    }),
+                    Some(_) => (),
+                }
+            }
+            if missing.is_empty() && outdated.is_empty() {
+                continue;
+            }
+            report.push(TranslationStatus {
+                post: post.clone(),
+                key: key.to_owned(),
+                original: Translation {
+                    language: original,
+                    slug,
+                    translated: true,
+                },
+                missing,
+                outdated,
+            });
+        }
+        report.sort_by(|a, b| a.post.cmp(&b.post).then(a.key.cmp(&b.key)));
+        report
+    }
+}
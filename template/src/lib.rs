@@ -63,8 +63,12 @@ impl Templates {
         }
     }
 
-    /// blocking operation to reload the template to match the latest edit
+    /// blocking operation to reload the template to match the latest edit,
+    /// reloaded on a copy so the old templates keep serving when the new one broken
     pub async fn reload(&self) -> Result<()> {
-        self.write().await.full_reload()
+        let mut tera = self.read().await.clone();
+        tera.full_reload()?;
+        *self.write().await = tera;
+        Ok(())
     }
 }